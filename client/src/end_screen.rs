@@ -0,0 +1,78 @@
+use common::graphics::{Color as BarColor, RenderTarget as CommonRenderTrait};
+use common::protocol::{MatchStats, PlayerStats};
+use sfml::graphics::{RenderTarget, Color};
+use sfml::window::event::Event;
+use sfml::window::Key;
+use render::SfRender;
+use text::draw_text;
+
+const OWN_COLOR: BarColor = [0, 255, 255, 255];
+const ENEMY_COLOR: BarColor = [255, 64, 64, 255];
+const TEXT_COLOR: BarColor = [255, 255, 255, 255];
+
+//label, own and enemy value of every row of bars
+fn rows(stats: &MatchStats, player: usize) -> Vec<(String, u64, u64)> {
+    let (own, enemy) = (&stats.players[player], &stats.players[player ^ 1]);
+    let mut rows = Vec::new();
+    for i in 0..own.ships_spawned.len().max(enemy.ships_spawned.len()) {
+        let spawned = |p: &PlayerStats| p.ships_spawned.get(i).cloned().unwrap_or(0) as u64;
+        rows.push((format!("ships {}", i), spawned(own), spawned(enemy)));
+    }
+    rows.push(("kills".to_string(), own.kills as u64, enemy.kills as u64));
+    let mut kinds: Vec<&String> = Vec::new();
+    for &(ref kind, _) in own.damage_dealt.iter().chain(enemy.damage_dealt.iter()) {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    for kind in kinds {
+        let dealt = |p: &PlayerStats| p.damage_dealt.iter().find(|&&(ref k, _)| k == kind).map(|&(_, d)| d).unwrap_or(0);
+        rows.push((format!("{} damage", kind), dealt(own), dealt(enemy)));
+    }
+    rows.push(("mothership damage".to_string(), own.mothership_damage, enemy.mothership_damage));
+    rows
+}
+
+fn draw_rect<T: CommonRenderTrait>(rt: &mut T, (x1, y1): (f32, f32), (x2, y2): (f32, f32), col: BarColor) {
+    rt.draw_triangle(&[(x1, y1), (x2, y1), (x2, y2)], col);
+    rt.draw_triangle(&[(x1, y1), (x2, y2), (x1, y2)], col);
+}
+
+//shows the stats of both players as labeled bars until a key is pressed, tick_length is in ms
+pub fn show(win: &mut SfRender, stats: &MatchStats, player: usize, won: bool, tick_length: u32) {
+    info!("game over after {} ticks, won: {}, stats: {:?}", stats.duration, won, stats);
+    let rows = rows(stats, player);
+    let secs = stats.duration as u64 * tick_length as u64 / 1000;
+    let title = format!("{}  time {}:{:02}", if won { "victory" } else { "defeat" }, secs / 60, secs % 60);
+    while win.is_open() {
+        for evt in win.events() {
+            match evt {
+                Event::KeyPressed { code: Key::Escape, .. } | Event::KeyPressed { code: Key::Return, .. } => return,
+                Event::Closed => {
+                    win.win.close();
+                    return;
+                },
+                _ => {}
+            }
+        }
+        let w = win.get_view().get_size().x;
+        let h = win.get_view().get_size().y;
+        let row_h = h / (rows.len() + 1) as f32;
+        //labels are at most about 20 characters
+        let size = (row_h * 0.3).min(w * 0.018);
+        win.clear(&Color::black());
+        draw_rect(win, (0., 0.), (w, row_h * 0.5), if won { OWN_COLOR } else { ENEMY_COLOR });
+        draw_text(win, &title, (w * 0.02, row_h * 0.6), size, TEXT_COLOR);
+        for (i, &(ref label, o, e)) in rows.iter().enumerate() {
+            let max = o.max(e).max(1) as f32;
+            let y = row_h * (i + 1) as f32;
+            draw_text(win, label, (w * 0.02, y + row_h * 0.35), size, TEXT_COLOR);
+            for &(value, top, col) in &[(o, 0.1, OWN_COLOR), (e, 0.55, ENEMY_COLOR)] {
+                let end = w * (0.4 + 0.45 * value as f32 / max);
+                draw_rect(win, (w * 0.4, y + row_h * top), (end, y + row_h * (top + 0.35)), col);
+                draw_text(win, &value.to_string(), (end + size * 0.5, y + row_h * top), size, TEXT_COLOR);
+            }
+        }
+        win.display();
+    }
+}
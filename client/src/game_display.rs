@@ -1,19 +1,23 @@
 use game_manager::GameManager;
 use key_manager::{Action, KeyManager};
 use common::game::Game;
-use common::graphics::RenderTarget as CommonRenderTrait;
+use common::graphics::{Color as HudColor, RenderTarget as CommonRenderTrait};
 use sfml::graphics::{RenderTarget, Color};
 use sfml::window::event::Event;
 use sfml::window::Key;
 use std::cmp::{min, max};
 use sfml::system::Clock;
 use render::SfRender;
+use end_screen;
+use text::draw_text;
 use common::serde_json;
 use common::graphics::TransformRender;
 use common::serde::de::Error as ErrorTrait;
 
 type V2 = [f32; 2];
 
+const HUD_COLOR: HudColor = [255, 255, 255, 255];
+
 #[derive(Debug)]
 pub enum RunResult {
     IoError(serde_json::Error),
@@ -73,13 +77,14 @@ pub fn run(win: &mut SfRender,
         }
         match game.manager.do_ticks(game.game) {
             Err(e) => return RunResult::IoError(e),
-            Ok(true) => match game.game.winner() {
-                Some(p) => if p == player {
-                    return RunResult::Win
-                } else {
-                    return RunResult::Lose
-                },
-                None => return RunResult::IoError(serde_json::Error::custom("unexpected end from server")),
+            Ok(true) => {
+                let stats = game.manager.stats().cloned().unwrap_or_default();
+                let won = match game.game.winner() {
+                    Some(p) => p == player,
+                    None => return RunResult::IoError(serde_json::Error::custom("unexpected end from server")),
+                };
+                end_screen::show(win, &stats, player, won, game.game.config().tick_length);
+                return if won { RunResult::Win } else { RunResult::Lose };
             },
             Ok(false) => {}
         }
@@ -121,6 +126,41 @@ fn handle_event(game: &mut GameView, evt: Event) -> EventResult {
                                 return EventResult::IoError(e);
                             }
                         }
+                        Some(&Action::MoveShipUp) => {
+                            if game.selected > 0 {
+                                let lane = game.selected - 1;
+                                if let Err(e) = move_front_ship(game, lane) {
+                                    return EventResult::IoError(e);
+                                }
+                            }
+                        }
+                        Some(&Action::MoveShipDown) => {
+                            if game.selected + 1 < game.game.lane_count() {
+                                let lane = game.selected + 1;
+                                if let Err(e) = move_front_ship(game, lane) {
+                                    return EventResult::IoError(e);
+                                }
+                            }
+                        }
+                        Some(&Action::SetStance(stance)) => {
+                            if let Err(e) = game.manager.set_stance(game.selected, stance) {
+                                return EventResult::IoError(e);
+                            }
+                        }
+                        Some(&Action::UseAbility { ship_type, ability }) => {
+                            if let Err(e) = game.manager.use_ability(game.selected, ship_type, ability) {
+                                return EventResult::IoError(e);
+                            }
+                        }
+                        Some(&Action::Research(upgrade)) => {
+                            //research keys may be bound to more upgrades than the server config has
+                            if upgrade >= game.game.config().upgrades.len() {
+                                return EventResult::None;
+                            }
+                            if let Err(e) = game.manager.research(upgrade) {
+                                return EventResult::IoError(e);
+                            }
+                        }
                         None => {}
                     }
                 }
@@ -141,6 +181,13 @@ fn handle_event(game: &mut GameView, evt: Event) -> EventResult {
     EventResult::None
 }
 
+fn move_front_ship(game: &mut GameView, lane: usize) -> Result<(), serde_json::Error> {
+    match game.game.front_ship(game.player, game.selected) {
+        Some(ship) => game.manager.move_ship(ship, lane),
+        None => Ok(()),
+    }
+}
+
 fn scroll(game: &mut GameView, dist: f32) {
     game.scroll += dist;
     bound_scroll(game);
@@ -182,5 +229,22 @@ fn draw(win: &mut SfRender, game: &GameView) {
         let mut render = TransformRender::new(win, move |(x, y)| ((x - x_translate) * x_scale, y * y_scale));
         game.game.draw(&mut render);
     }
+    draw_hud(win, game);
     win.display();
+}
+
+//credits and research progress of the player in the top left corner
+fn draw_hud(win: &mut SfRender, game: &GameView) {
+    let size = win.get_view().get_size().y * 0.02;
+    let research = game.game.research(game.player);
+    let progress = match research.current() {
+        Some((upgrade, left)) => {
+            let secs = (left as u64 * game.game.config().tick_length as u64 + 999) / 1000;
+            format!("research {}: {}s", upgrade, secs)
+        },
+        None => "research -".to_string(),
+    };
+    let text = format!("credits {}  {}  done {}/{}", game.game.credits(game.player), progress,
+                       research.done().len(), game.game.config().upgrades.len());
+    draw_text(win, &text, (size, size), size, HUD_COLOR);
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 use sfml::system::Clock;
-use common::game::ship::BaseShipBuilder;
+use common::game::ship::{BaseShipBuilder, ShipId, Stance};
 use common::game::Game;
 use common::protocol::*;
 use std::io;
@@ -8,7 +8,8 @@ use common::serde_json::Error;
 use std::net::TcpStream;
 
 pub struct GameManager {
-    end_received: bool,
+    //stats sent with the end of the game
+    end: Option<MatchStats>,
     clock: Clock,
     skip_ticks: usize,
     stream: BufStream<TcpStream>,
@@ -51,16 +52,8 @@ impl FrameManager {
     fn try_tick(&mut self, game: &mut Game, builders: &[Vec<BaseShipBuilder>; 2]) -> Result<bool, Error> {
         if let Some(frame) = self.frames.pop_front() {
             for evt in frame.iter() {
-                match *evt {
-                    ServerEvent::SpawnShip { player, lane, id } => {
-                        assert_data!( player <= 1,"invalid player in SpawnShip event");
-                        assert_data!( lane < game.lane_count(),"invalid lane in SpawnShip event");
-                        if let Some(builder_ref) = builders[player].get(id) {
-                            game.push_ship(builder_ref.build(), player, lane);
-                        } else {
-                            assert_data!(false,"invalid ship in SpawnShip event");
-                        }
-                    }
+                if let Err(e) = evt.apply(game, builders) {
+                    assert_data!(false, e);
                 }
             }
             game.tick();
@@ -75,7 +68,7 @@ impl FrameManager {
 impl GameManager {
     pub fn new(builders: [Vec<BaseShipBuilder>; 2], stream: BufStream<TcpStream>) -> Self {
         GameManager {
-            end_received: false,
+            end: None,
             clock: Clock::new(),
             skip_ticks: 0,
             stream: stream,
@@ -87,7 +80,7 @@ impl GameManager {
         }
     }
     pub fn do_ticks(&mut self, game: &mut Game) -> Result<bool, Error> {
-        while !self.end_received {
+        while self.end.is_none() {
             match self.stream.read() {
                 Some(Ok(ServerGame::Update(msg))) => {
                     self.frames.push_report(msg)?;
@@ -96,22 +89,44 @@ impl GameManager {
                     use common::serde::de::Error;
                     return Err(Error::custom("other disconnect"))
                 },
-                Some(Ok(ServerGame::End)) => {
-                    self.end_received = true;
+                Some(Ok(ServerGame::Desync { tick })) => {
+                    use common::serde::de::Error;
+                    return Err(Error::custom(format!("desync at tick {}", tick)))
+                },
+                Some(Ok(ServerGame::End(stats))) => {
+                    self.end = Some(stats);
                 }
                 Some(Err(e)) => return Err(e),
                 None => break
             }
         }
-        while self.frames.next_tick + self.skip_ticks < self.clock.get_elapsed_time().as_milliseconds() as usize / 20 {
+        let tick_length = game.config().tick_length as usize;
+        while self.frames.next_tick + self.skip_ticks < self.clock.get_elapsed_time().as_milliseconds() as usize / tick_length {
             if !self.frames.try_tick(game, &self.builders)? {
                 self.skip_ticks += 1;
+            } else if self.frames.next_tick % STATE_HASH_INTERVAL == 0 {
+                self.stream.write(&ClientGame::StateHash { tick: self.frames.next_tick, hash: game.state_hash() })?;
             }
         }
-        Ok(self.end_received && self.frames.frames.is_empty())
+        Ok(self.end.is_some() && self.frames.frames.is_empty())
+    }
+    pub fn stats(&self) -> Option<&MatchStats> {
+        self.end.as_ref()
     }
     #[allow(unused_variables)]
     pub fn spawn_ship(&mut self, player: usize, lane: usize, builder_id: usize) -> Result<(), Error> {
         self.stream.write(&ClientGame::SpawnShip { id: builder_id, lane: lane })
     }
+    pub fn move_ship(&mut self, ship: ShipId, lane: usize) -> Result<(), Error> {
+        self.stream.write(&ClientGame::MoveShip { ship: ship, lane: lane })
+    }
+    pub fn set_stance(&mut self, lane: usize, stance: Stance) -> Result<(), Error> {
+        self.stream.write(&ClientGame::SetStance { lane: lane, stance: stance })
+    }
+    pub fn use_ability(&mut self, lane: usize, ship_type: usize, ability: usize) -> Result<(), Error> {
+        self.stream.write(&ClientGame::UseAbility { lane: lane, ship_type: ship_type, ability: ability })
+    }
+    pub fn research(&mut self, upgrade: usize) -> Result<(), Error> {
+        self.stream.write(&ClientGame::Research { upgrade: upgrade })
+    }
 }
@@ -1,8 +1,18 @@
 use std::collections::BTreeMap;
 use sfml::window::Key;
+use common::game::ship::Stance;
 
 pub type KeyManager = BTreeMap<Key, Action>;
 
 pub enum Action {
-    SpawnShip(usize)
+    SpawnShip(usize),
+    //moves the front ship of the selected lane to the lane above/below
+    MoveShipUp,
+    MoveShipDown,
+    //gives the selected lane a new order
+    SetStance(Stance),
+    //triggers an ability of all ships of a type in the selected lane
+    UseAbility { ship_type: usize, ability: usize },
+    //starts researching an upgrade of the game config
+    Research(usize),
 }
\ No newline at end of file
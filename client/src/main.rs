@@ -9,6 +9,8 @@ mod game_display;
 mod game_manager;
 mod key_manager;
 mod render;
+mod end_screen;
+mod text;
 
 use common::*;
 use sfml::graphics::*;
@@ -21,6 +23,7 @@ fn main() {
     env_logger::init().unwrap();
     let builder1: game::ship::BaseShipBuilder = serde_json::from_str(include_str!("plasma_ship.json")).unwrap();
     let builder2: game::ship::BaseShipBuilder = serde_json::from_str(include_str!("laser_ship.json")).unwrap();
+    let mothership: game::ship::MothershipBuilder = serde_json::from_str(include_str!("mothership.json")).unwrap();
     let mut window = RenderWindow::new(VideoMode::new_init(600, 600, 32),
                                        "space game",
                                        window_style::CLOSE | window_style::RESIZE,
@@ -29,12 +32,28 @@ fn main() {
     let mut render = SfRender::new(window, "textures/".into());
     let mut keys = KeyManager::new();
     keys.insert(Key::Z, key_manager::Action::SpawnShip(0));
+    keys.insert(Key::Q, key_manager::Action::MoveShipUp);
+    keys.insert(Key::A, key_manager::Action::MoveShipDown);
+    keys.insert(Key::X, key_manager::Action::SetStance(game::ship::Stance::Advance));
+    keys.insert(Key::C, key_manager::Action::SetStance(game::ship::Stance::Hold));
+    keys.insert(Key::V, key_manager::Action::SetStance(game::ship::Stance::Retreat));
+    keys.insert(Key::Num1, key_manager::Action::UseAbility { ship_type: 0, ability: 0 });
+    keys.insert(Key::Num2, key_manager::Action::UseAbility { ship_type: 0, ability: 1 });
+    keys.insert(Key::R, key_manager::Action::Research(0));
+    keys.insert(Key::T, key_manager::Action::Research(1));
     let address = args().nth(1).unwrap().parse().unwrap();
-    let builders = vec![builder1, builder2];
+    let start = protocol::ClientStart {
+        ships: vec![builder1, builder2],
+        mothership: mothership,
+    };
     if let Some(arg2) = args().nth(2) {
         let join_id = arg2.parse().unwrap();
-        println!("{:?}", play_server::server_join(&mut render, &address, builders, &mut keys, join_id));
+        println!("{:?}", play_server::server_join(&mut render, &address, start, &mut keys, join_id));
     } else {
-        println!("{:?}", play_server::server_create(&mut render, &address, builders, &mut keys));
+        let config = game::GameConfig {
+            upgrades: serde_json::from_str(include_str!("upgrades.json")).unwrap(),
+            ..Default::default()
+        };
+        println!("{:?}", play_server::server_create(&mut render, &address, config, start, &mut keys));
     }
 }
\ No newline at end of file
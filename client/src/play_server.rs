@@ -2,9 +2,8 @@ use sfml::graphics::RenderWindow;
 use sfml::window::event::Event;
 use sfml::window::Key;
 use render::SfRender;
-use common::game::ship::BaseShipBuilder;
 use common::protocol::*;
-use common::game::Game;
+use common::game::{Game, GameConfig};
 use key_manager::KeyManager;
 use game_manager::GameManager;
 use game_display::{run as run_display, RunResult};
@@ -49,14 +48,15 @@ macro_rules! message_error {
 
 pub fn server_create(window: &mut SfRender,
                      addr: &SocketAddr,
-                     own_builders: Vec<BaseShipBuilder>,
+                     config: GameConfig,
+                     own_start: ClientStart,
                      keys: &mut KeyManager)
                      -> RunResult {
     let mut stream = match create_stream(addr) {
         Ok(stream) => stream,
         Err(e) => return RunResult::IoError(e.into()),
     };
-    if let Err(e) = stream.write(&ClientJoin::Create) {
+    if let Err(e) = stream.write(&ClientJoin::Create(config)) {
         return RunResult::IoError(e);
     }
     let join_id;
@@ -72,22 +72,24 @@ pub fn server_create(window: &mut SfRender,
     }
     println!("join id:{}", join_id);
     let player_num;
+    let config;
     loop {
         let msg = stream.read();
-        if let Some(Ok(ServerJoin::Start(player))) = msg {
+        if let Some(Ok(ServerJoin::Start(player, game_config))) = msg {
             player_num = player;
+            config = game_config;
             info!("starting game as {}", player);
             break;
         } else {
             message_error!(window,msg);
         }
     }
-    run(window, stream, own_builders, keys, player_num)
+    run(window, stream, own_start, keys, player_num, config)
 }
 
 pub fn server_join(window: &mut SfRender,
                    addr: &SocketAddr,
-                   own_builders: Vec<BaseShipBuilder>,
+                   own_start: ClientStart,
                    keys: &mut KeyManager,
                    join_id: u32)
                    -> RunResult {
@@ -99,14 +101,16 @@ pub fn server_join(window: &mut SfRender,
         return RunResult::IoError(e);
     }
     let player_num;
+    let config;
     loop {
         match stream.read() {
             Some(Ok(ServerJoin::JoinFail)) => {
                 return RunResult::IoError(io::Error::new
                     (io::ErrorKind::InvalidData, format!("cannot join game {} on server {:?}", join_id, addr)).into());
             },
-            Some(Ok(ServerJoin::Start(player))) => {
+            Some(Ok(ServerJoin::Start(player, game_config))) => {
                 player_num = player;
+                config = game_config;
                 break;
             },
             Some(Ok(msg)) => {
@@ -119,7 +123,7 @@ pub fn server_join(window: &mut SfRender,
             None => {}
         }
     }
-    run(window, stream, own_builders, keys, player_num)
+    run(window, stream, own_start, keys, player_num, config)
 }
 
 fn create_stream(addr: &SocketAddr) -> Result<BufStream<TcpStream>, io::Error> {
@@ -131,29 +135,34 @@ fn create_stream(addr: &SocketAddr) -> Result<BufStream<TcpStream>, io::Error> {
 
 fn run(window: &mut SfRender,
        mut stream: BufStream<TcpStream>,
-       own_builders: Vec<BaseShipBuilder>,
+       own_start: ClientStart,
        keys: &mut KeyManager,
-       player: usize)
+       player: usize,
+       config: GameConfig)
        -> RunResult {
-    let own_start = ClientStart { ships: own_builders };
     if let Err(e) = stream.write(&own_start) {
         return RunResult::IoError(e);
     }
-    let other_builders;
+    let other_start: ClientStart;
     loop {
         let msg = stream.read();
-        if let Some(Ok(ClientStart { ships })) = msg {
-            other_builders = ships;
+        if let Some(Ok(start)) = msg {
+            other_start = start;
             break;
         } else {
             message_error!(window,msg)
         }
     }
-    let mut game = Game::new(4, 10_000);
+    if !config.is_valid() {
+        return RunResult::IoError(io::Error::new(io::ErrorKind::InvalidData, format!("invalid game config {:?}", config)).into());
+    }
+    let mut game = Game::new(&config);
+    game.set_mothership(player, &own_start.mothership);
+    game.set_mothership(player ^ 1, &other_start.mothership);
     let mut game_manager = if player == 0 {
-        GameManager::new([own_start.ships, other_builders], stream)
+        GameManager::new([own_start.ships, other_start.ships], stream)
     } else {
-        GameManager::new([other_builders, own_start.ships], stream)
+        GameManager::new([other_start.ships, own_start.ships], stream)
     };
     run_display(window, &mut game, &mut game_manager, player, keys)
 }
@@ -0,0 +1,58 @@
+use common::graphics::{Color, RenderTarget};
+
+//line segments "x1y1x2y2" of every glyph on a 4x6 grid, y pointing down
+fn glyph(c: char) -> &'static str {
+    match c.to_ascii_uppercase() {
+        '0' => "0040 4046 4606 0600 0640",
+        '1' => "1120 2026 1636",
+        '2' => "0040 4043 4303 0306 0646",
+        '3' => "0040 4046 4606 1343",
+        '4' => "0003 0343 4046",
+        '5' | 'S' => "4000 0003 0343 4346 4606",
+        '6' => "4000 0006 0646 4643 4303",
+        '7' => "0040 4016",
+        '8' => "0040 4046 4606 0600 0343",
+        '9' => "4303 0300 0040 4046 4606",
+        'A' => "0602 0220 2042 4246 0343",
+        'B' => "0006 0030 3041 4142 4233 0333 3344 4445 4536 3606",
+        'C' => "4000 0006 0646",
+        'D' => "0006 0030 3041 4145 4536 3606",
+        'E' => "4000 0006 0646 0333",
+        'F' => "4000 0006 0333",
+        'G' => "4000 0006 0646 4643 4323",
+        'H' => "0006 4046 0343",
+        'I' => "0040 2026 0646",
+        'J' => "0040 3036 3606 0604",
+        'K' => "0006 0340 0346",
+        'L' => "0006 0646",
+        'M' => "0600 0023 2340 4046",
+        'N' => "0600 0046 4640",
+        'O' => "0040 4046 4606 0600",
+        'P' => "0600 0040 4043 4303",
+        'Q' => "0040 4046 4606 0600 2446",
+        'R' => "0600 0040 4043 4303 2346",
+        'T' => "0040 2026",
+        'U' => "0006 0646 4640",
+        'V' => "0026 2640",
+        'W' => "0006 0623 2346 4640",
+        'X' => "0046 4006",
+        'Y' => "0023 2340 2326",
+        'Z' => "0040 4006 0646",
+        ':' => "2122 2425",
+        '-' => "0343",
+        '.' => "2526",
+        _ => "",
+    }
+}
+
+//draws text with lines, size is the height of a character and also its advance
+pub fn draw_text<T: RenderTarget>(rt: &mut T, text: &str, (x, y): (f32, f32), size: f32, col: Color) {
+    let scale = size / 6.;
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as f32 * size;
+        for seg in glyph(c).split(' ').filter(|s| !s.is_empty()) {
+            let p: Vec<f32> = seg.bytes().map(|b| (b - b'0') as f32 * scale).collect();
+            rt.draw_line((left + p[0], y + p[1]), (left + p[2], y + p[3]), col);
+        }
+    }
+}
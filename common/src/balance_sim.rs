@@ -0,0 +1,132 @@
+extern crate common;
+#[macro_use]
+extern crate serde_derive;
+
+use std::env::args;
+use std::fs::File;
+use std::io::BufReader;
+use std::collections::VecDeque;
+use common::game::{Game, GameConfig};
+use common::game::ship::BaseShipBuilder;
+use common::serde::Deserialize;
+use common::serde_json;
+
+const MAX_TICKS: usize = 200_000;
+
+#[derive(Deserialize, Debug)]
+struct Spawn {
+    tick: usize,
+    side: usize,
+    lane: usize,
+    ship: usize,
+}
+
+struct MatchResult {
+    ticks: usize,
+    //winning side
+    winner: Option<usize>,
+    mothership_health: [u32; 2],
+}
+
+//xorshift, only used to jitter spawn times reproducibly
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn load<T: Deserialize>(path: &str) -> T {
+    let file = File::open(path).expect(&format!("cannot open {:?}", path));
+    serde_json::from_reader(BufReader::new(file)).expect(&format!("cannot parse {:?}", path))
+}
+
+//side 0 plays as player `swap as usize`, spawns that cannot be afforded yet wait for enough credits
+fn run_match(config: &GameConfig,
+             sides: &[Vec<BaseShipBuilder>; 2],
+             script: &[Spawn],
+             swap: bool,
+             jitter: usize,
+             rng: &mut Rng)
+             -> MatchResult {
+    let mut game = Game::new(config);
+    let mut script: Vec<(usize, &Spawn)> = script.iter()
+        .map(|s| (s.tick + if jitter > 0 { rng.next() as usize % (jitter + 1) } else { 0 }, s))
+        .collect();
+    script.sort_by_key(|s| s.0);
+    let mut script = script.into_iter().peekable();
+    let mut pending = [VecDeque::new(), VecDeque::new()];
+    let mut tick = 0;
+    while tick < MAX_TICKS && game.winner().is_none() {
+        while let Some(&(t, spawn)) = script.peek() {
+            if t > tick {
+                break;
+            }
+            pending[spawn.side].push_back(spawn);
+            script.next();
+        }
+        for side in 0..2 {
+            let player = side ^ swap as usize;
+            while let Some(spawn) = pending[side].pop_front() {
+                let builder = &sides[side][spawn.ship];
+                if game.try_spend(player, builder.cost()) {
+                    game.push_ship(builder.build(spawn.ship), player, spawn.lane);
+                } else {
+                    pending[side].push_front(spawn);
+                    break;
+                }
+            }
+        }
+        game.tick();
+        tick += 1;
+    }
+    let side_player = [swap as usize, !swap as usize];
+    MatchResult {
+        ticks: tick,
+        winner: game.winner().map(|p| p ^ swap as usize),
+        mothership_health: [game.mothership_health(side_player[0]), game.mothership_health(side_player[1])],
+    }
+}
+
+fn main() {
+    let usage = "usage: balance_sim <ships a> <ships b> <spawn script> [matches] [spawn jitter] [game config]";
+    let sides: [Vec<BaseShipBuilder>; 2] = [
+        load(&args().nth(1).expect(usage)),
+        load(&args().nth(2).expect(usage))
+    ];
+    let script: Vec<Spawn> = load(&args().nth(3).expect(usage));
+    let matches: usize = args().nth(4).map(|s| s.parse().expect(usage)).unwrap_or(10);
+    let jitter: usize = args().nth(5).map(|s| s.parse().expect(usage)).unwrap_or(0);
+    let config: GameConfig = args().nth(6).map(|path| load(&path)).unwrap_or_default();
+    assert!(config.is_valid(), "invalid game config {:?}", config);
+    for s in script.iter() {
+        assert!(s.side < 2 && s.ship < sides[s.side].len() && s.lane < config.lane_count, "invalid spawn {:?}", s);
+    }
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut wins = [0, 0];
+    let mut total_ticks = 0;
+    let mut total_health = [0u64, 0];
+    for i in 0..matches {
+        let result = run_match(&config, &sides, &script, i % 2 == 1, jitter, &mut rng);
+        if let Some(side) = result.winner {
+            wins[side] += 1;
+        }
+        total_ticks += result.ticks;
+        total_health[0] += result.mothership_health[0] as u64;
+        total_health[1] += result.mothership_health[1] as u64;
+    }
+    let matches_f = matches.max(1) as f64;
+    println!("matches: {}", matches);
+    println!("wins: a {} ({:.1}%), b {} ({:.1}%), draws {}",
+             wins[0], wins[0] as f64 * 100. / matches_f,
+             wins[1], wins[1] as f64 * 100. / matches_f,
+             matches - wins[0] - wins[1]);
+    let avg_ticks = total_ticks as f64 / matches_f;
+    println!("average duration: {:.0} ticks ({:.1}s)", avg_ticks, avg_ticks * config.tick_length as f64 / 1000.);
+    println!("average remaining mothership health: a {:.0}, b {:.0}",
+             total_health[0] as f64 / matches_f, total_health[1] as f64 / matches_f);
+}
@@ -0,0 +1,69 @@
+use super::Upgrade;
+
+pub const MAX_LANES: usize = 16;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum WinMode {
+    //all lanes damage one mothership, the game ends when its health reaches 0
+    SharedHealth,
+    //every lane has its own mothership section, the game ends when this many sections of a player are destroyed
+    Sections(usize),
+}
+
+impl Default for WinMode {
+    fn default() -> Self {
+        WinMode::SharedHealth
+    }
+}
+
+//chosen by the creator of a game and sent to both players, so server and clients simulate the same game
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub lane_count: usize,
+    pub lane_length: i32,
+    pub mothership_health: u32,
+    //milliseconds per tick
+    pub tick_length: u32,
+    //ticks between updates sent by the server
+    pub update_interval: usize,
+    #[serde(default)]
+    pub win_mode: WinMode,
+    //upgrades players can research during the game
+    #[serde(default)]
+    pub upgrades: Vec<Upgrade>,
+}
+
+impl GameConfig {
+    pub fn is_valid(&self) -> bool {
+        self.lane_count > 0 && self.lane_count <= MAX_LANES
+            && self.lane_length > 0 && self.lane_length <= 1_000_000
+            && self.mothership_health > 0
+            && self.tick_length > 0 && self.tick_length <= 1_000
+            && self.update_interval > 0 && self.update_interval <= 1_000
+            && match self.win_mode {
+                WinMode::SharedHealth => true,
+                WinMode::Sections(n) => n > 0 && n <= self.lane_count,
+            }
+    }
+    //number of separate mothership health pools of each player
+    pub fn section_count(&self) -> usize {
+        match self.win_mode {
+            WinMode::SharedHealth => 1,
+            WinMode::Sections(_) => self.lane_count,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            lane_count: 4,
+            lane_length: 10_000,
+            mothership_health: 1_000_000,
+            tick_length: 20,
+            update_interval: 16,
+            win_mode: WinMode::SharedHealth,
+            upgrades: Vec::new(),
+        }
+    }
+}
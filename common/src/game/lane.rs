@@ -1,26 +1,34 @@
 use super::projectile::Projectile;
-use std::cell::{RefCell, Cell};
-use std::rc::Rc;
 use std::ops::FnMut;
 use super::ship::*;
+use super::{GameConfig, GameObserver};
 use std::ops::*;
 #[cfg(feature = "graphics")]
 use graphics;
 
 pub const LANE_HEIGHT: i32 = 1000;
+//ticks a ship needs to move to an adjacent lane
+pub const LANE_CHANGE_TIME: u32 = 50;
+//minimum distance a ship keeps to the ship in front of it, friendly or enemy
+pub const SHIP_SPACING: i32 = 100;
 
+//ids of the ships in a lane, the ships themselves are stored in the game's ShipArena
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Lane {
-    ships: Vec<Rc<RefCell<Ship>>>,
-    mothership: Rc<RefCell<Ship>>,
+    ships: Vec<ShipId>,
+    mothership: ShipId,
     len: i32,
     pos: usize,
     right_to_left: bool,
+    stance: Stance,
 }
 
 impl Lane {
-    pub fn new(mothership_health: Rc<Cell<u32>>, len: i32, id: usize, right_to_left: bool) -> Self {
+    pub fn new(config: &GameConfig, ships: &mut ShipArena, section: usize, id: usize, right_to_left: bool) -> Self {
+        let len = config.lane_length;
         let mothership = Mothership::new(
-            mothership_health,
+            right_to_left as usize,
+            section,
             if right_to_left { len } else { 0 }, id as i32 * LANE_HEIGHT + LANE_HEIGHT / 2
         );
         Lane {
@@ -28,23 +36,79 @@ impl Lane {
             len: len,
             pos: id,
             right_to_left: right_to_left,
-            mothership: Rc::new(RefCell::new(Ship::Mothership(mothership))),
+            stance: Stance::Advance,
+            mothership: ships.insert(Ship::Mothership(mothership)),
         }
     }
-    pub fn push(&mut self, mut s: BaseShip) {
+    pub fn push(&mut self, mut s: BaseShip, ships: &mut ShipArena) -> ShipId {
         s.lane_changed(self);
+        s.set_stance(self.stance);
         if self.right_to_left {
             s.set_pos_x(self.len);
         } else {
             s.set_pos_x(0);
         }
-        self.ships.push(Rc::new(RefCell::new(Ship::BaseShip(s))));
+        let id = ships.insert(Ship::BaseShip(s));
+        self.ships.push(id);
+        id
     }
-    pub fn tick<F: FnMut(Projectile)>(&mut self, other: &mut [Lane], push_projectile: &mut F) {
-        for s in self.ships.iter_mut() {
-            s.borrow_mut().tick(self.pos, other, push_projectile);
+    //removes the ship from the lane only, it stays in the arena
+    pub fn remove(&mut self, id: ShipId) -> bool {
+        match self.ships.iter().position(|&s| s == id) {
+            Some(i) => {
+                self.ships.remove(i);
+                true
+            }
+            None => false,
         }
-        self.ships.retain(|s| s.borrow().health() > 0);
+    }
+    //moves an existing ship into this lane, its id stays the same
+    pub fn transfer(&mut self, id: ShipId, ships: &mut ShipArena) {
+        if let Ship::BaseShip(ref mut b) = ships[id] {
+            b.start_transit(self);
+            b.set_stance(self.stance);
+        }
+        self.ships.push(id);
+    }
+    pub fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, other: &[Lane], ships: &mut ShipArena, sections: &mut [Vec<u32>; 2],
+                                                       push_projectile: &mut F, observer: &mut O) {
+        ships.tick(self.mothership, self, other, sections, push_projectile, observer);
+        for &s in self.ships.iter() {
+            ships.tick(s, self, other, sections, push_projectile, observer);
+        }
+        let (direction, index) = (self.direction(), self.pos);
+        self.ships.retain(|&s| {
+            if ships[s].health() > 0 {
+                return true;
+            }
+            ships.remove(s);
+            observer.ship_destroyed(direction, index, s);
+            false
+        });
+    }
+    pub fn stance(&self) -> Stance {
+        self.stance
+    }
+    //changes the order for the lane, ships entering the lane later follow it too
+    pub fn set_stance(&mut self, stance: Stance, ships: &mut ShipArena) {
+        self.stance = stance;
+        for &s in self.ships.iter() {
+            if let Ship::BaseShip(ref mut s) = ships[s] {
+                s.set_stance(stance);
+            }
+        }
+    }
+    //triggers an ability on every ship of the given type that has it ready, returns how many ships used it
+    pub fn use_ability(&mut self, ship_type: usize, ability: usize, ships: &mut ShipArena) -> usize {
+        let mut used = 0;
+        for &s in self.ships.iter() {
+            if let Ship::BaseShip(ref mut s) = ships[s] {
+                if s.ship_type() == ship_type && s.use_ability(ability) {
+                    used += 1;
+                }
+            }
+        }
+        used
     }
     pub fn distance(&self) -> i32 {
         self.len
@@ -56,18 +120,25 @@ impl Lane {
     pub fn right_to_left(&self) -> bool {
         self.right_to_left
     }
+    pub fn direction(&self) -> usize {
+        self.right_to_left as usize
+    }
+    pub fn index(&self) -> usize {
+        self.pos
+    }
     #[cfg(feature = "graphics")]
-    pub fn draw<T: graphics::RenderTarget>(&self, target: &mut T) {
-        for s in self.ships.iter() {
-            s.borrow().draw(target, self);
+    pub fn draw<T: graphics::RenderTarget>(&self, target: &mut T, ships: &ShipArena) {
+        ships[self.mothership].draw(target, self);
+        for &s in self.ships.iter() {
+            ships[s].draw(target, self);
         }
     }
-    pub fn mothership(&self) -> &Rc<RefCell<Ship>> {
-        &self.mothership
+    pub fn mothership(&self) -> ShipId {
+        self.mothership
     }
 }
 
 impl Deref for Lane {
-    type Target = [Rc<RefCell<Ship>>];
+    type Target = [ShipId];
     fn deref(&self) -> &Self::Target { &self.ships }
 }
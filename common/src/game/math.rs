@@ -0,0 +1,43 @@
+//! Integer helpers for the simulation.
+//!
+//! Server and clients run `Game::tick` independently, so everything that
+//! influences game state must produce bit-identical results on every platform.
+//! Floating point is therefore not allowed anywhere in the simulation.
+
+//multiplies by m2 / u32::MAX
+pub fn mul_frac(m1: u32, m2: u32) -> u32 {
+    use std::u32::MAX;
+    ((m1 as u64 * m2 as u64) / MAX as u64) as u32
+}
+
+pub fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = x / 2 + (x & 1);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+pub fn vec_len(x: i32, y: i32) -> i32 {
+    let (x, y) = (x as i64, y as i64);
+    isqrt((x * x + y * y) as u64) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::isqrt;
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..10_000u64 {
+            let r = isqrt(n);
+            assert!(r * r <= n && (r + 1) * (r + 1) > n, "isqrt({}) = {}", n, r);
+        }
+        assert_eq!(isqrt(::std::u64::MAX), ::std::u32::MAX as u64);
+    }
+}
@@ -1,45 +1,190 @@
 pub mod ship;
 mod lane;
 mod projectile;
+mod math;
+mod state_hash;
+mod snapshot;
+mod config;
+mod research;
+mod observer;
 
-use std::cell::Cell;
-use std::rc::Rc;
+use std::hash::{Hash, Hasher};
 use self::projectile::Projectile;
-use self::ship::{BaseShip, MOTHERSHIP_MAX_HEALTH};
+use self::ship::{BaseShip, MothershipBuilder, Ship, ShipArena, ShipTrait, ShipId, Stance};
 use self::lane::*;
+use self::state_hash::StateHasher;
+pub use self::snapshot::*;
+pub use self::config::*;
+pub use self::research::*;
+pub use self::observer::GameObserver;
 #[cfg(feature = "graphics")]
 use graphics;
 
+pub const STARTING_CREDITS: u32 = 1_000;
+pub const CREDIT_INCOME: u32 = 1;
+
+//Send only without the graphics feature, ship and projectile sprites share their textures through Rc
 pub struct Game {
-    mothership_health: [Rc<Cell<u32>>; 2],
+    config: GameConfig,
+    credits: [u32; 2],
+    //all ships including motherships, lanes refer to them by id
+    ships: ShipArena,
     lanes: [Vec<Lane>; 2],
+    //health of the mothership sections of both players, shared by the motherships of all lanes in a section
+    sections: [Vec<u32>; 2],
+    research: [Research; 2],
     projectiles: Vec<Projectile>,
+    //number of ships spawned so far
+    spawned: u64,
 }
 
 impl Game {
-    pub fn push_ship(&mut self, s: BaseShip, direction: usize, lane: usize) {
-        self.lanes[direction][lane].push(s);
+    pub fn push_ship(&mut self, s: BaseShip, direction: usize, lane: usize) -> ShipId {
+        self.push_ship_observed(s, direction, lane, &mut ())
+    }
+    pub fn push_ship_observed<O: GameObserver>(&mut self, mut s: BaseShip, direction: usize, lane: usize, observer: &mut O) -> ShipId {
+        for &u in self.research[direction].done() {
+            s.upgrade(&self.config.upgrades[u].effect);
+        }
+        s.set_spawn(self.spawned);
+        self.spawned += 1;
+        let ship_type = s.ship_type();
+        let id = self.lanes[direction][lane].push(s, &mut self.ships);
+        observer.ship_spawned(direction, lane, id, ship_type);
+        id
+    }
+    //moves a ship of the given player to an adjacent lane, returns false if the move isn't possible
+    pub fn move_ship(&mut self, player: usize, ship: ShipId, lane: usize) -> bool {
+        if lane >= self.lane_count() {
+            return false;
+        }
+        let from = match self.lanes[player].iter().position(|l| l.iter().any(|&s| s == ship && !self.ships[s].in_transit())) {
+            Some(from) => from,
+            None => return false,
+        };
+        if from + 1 != lane && lane + 1 != from {
+            return false;
+        }
+        self.lanes[player][from].remove(ship);
+        self.lanes[player][lane].transfer(ship, &mut self.ships);
+        true
+    }
+    pub fn set_stance(&mut self, player: usize, lane: usize, stance: Stance) -> bool {
+        match self.lanes[player].get_mut(lane) {
+            Some(l) => {
+                l.set_stance(stance, &mut self.ships);
+                true
+            }
+            None => false,
+        }
+    }
+    //returns the number of ships that used the ability, 0 for invalid lanes
+    pub fn use_ability(&mut self, player: usize, lane: usize, ship_type: usize, ability: usize) -> usize {
+        match self.lanes[player].get_mut(lane) {
+            Some(l) => l.use_ability(ship_type, ability, &mut self.ships),
+            None => 0,
+        }
     }
-    pub fn new(size: usize, length: i32) -> Self {
-        assert!(size > 0);
+    //the ship of the given player furthest ahead in a lane that isn't already changing lanes
+    pub fn front_ship(&self, player: usize, lane: usize) -> Option<ShipId> {
+        let l = &self.lanes[player][lane];
+        let sign = if l.right_to_left() { -1 } else { 1 };
+        l.iter()
+            .cloned()
+            .filter(|&s| !self.ships[s].in_transit())
+            .max_by_key(|&s| self.ships[s].pos_x() * sign)
+    }
+    //arms the motherships of every lane of a player, called once at match start
+    pub fn set_mothership(&mut self, player: usize, builder: &MothershipBuilder) {
+        for l in self.lanes[player].iter() {
+            if let Ship::Mothership(ref mut m) = self.ships[l.mothership()] {
+                m.set_weapons(builder.weapons.clone());
+            }
+        }
+    }
+    pub fn new(config: &GameConfig) -> Self {
+        assert!(config.is_valid());
+        let size = config.lane_count;
         let mut g = Game {
-            mothership_health: [Rc::new(Cell::new(MOTHERSHIP_MAX_HEALTH)), Rc::new(Cell::new(MOTHERSHIP_MAX_HEALTH))],
+            config: config.clone(),
+            credits: [STARTING_CREDITS; 2],
+            ships: ShipArena::default(),
             lanes: [Vec::with_capacity(size), Vec::with_capacity(size)],
+            sections: [vec![config.mothership_health; config.section_count()], vec![config.mothership_health; config.section_count()]],
+            research: [Research::default(), Research::default()],
             projectiles: Vec::new(),
+            spawned: 0,
         };
         for i in 0..size {
-            g.lanes[0].push(Lane::new(g.mothership_health[0].clone(), length, i, false));
-            g.lanes[1].push(Lane::new(g.mothership_health[1].clone(), length, i, true));
+            let section = i.min(config.section_count() - 1);
+            let lane = Lane::new(config, &mut g.ships, section, i, false);
+            g.lanes[0].push(lane);
+            let lane = Lane::new(config, &mut g.ships, section, i, true);
+            g.lanes[1].push(lane);
         };
         g
     }
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+    pub fn credits(&self, player: usize) -> u32 {
+        self.credits[player]
+    }
+    pub fn try_spend(&mut self, player: usize, amount: u32) -> bool {
+        if self.credits[player] >= amount {
+            self.credits[player] -= amount;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn ships(&self) -> &ShipArena {
+        &self.ships
+    }
+    //ships can't be added or removed through this, so lanes always refer to existing ships
+    pub fn ship_mut(&mut self, id: ShipId) -> Option<&mut Ship> {
+        self.ships.get_mut(id)
+    }
+    pub fn research(&self, player: usize) -> &Research {
+        &self.research[player]
+    }
+    //spends the cost and starts researching, returns false if the upgrade can't be researched now
+    pub fn start_research(&mut self, player: usize, upgrade: usize) -> bool {
+        let (cost, time) = match self.config.upgrades.get(upgrade) {
+            Some(u) if self.research[player].can_start(upgrade) => (u.cost, u.research_time),
+            _ => return false,
+        };
+        if !self.try_spend(player, cost) {
+            return false;
+        }
+        self.research[player].start(upgrade, time);
+        true
+    }
     pub fn tick(&mut self) {
+        self.tick_observed(&mut ())
+    }
+    pub fn tick_observed<O: GameObserver>(&mut self, observer: &mut O) {
+        for c in self.credits.iter_mut() {
+            *c = c.saturating_add(CREDIT_INCOME);
+        }
+        for player in 0..2 {
+            if let Some(u) = self.research[player].tick() {
+                let upgrade = &self.config.upgrades[u];
+                if upgrade.upgrade_existing {
+                    for &s in self.lanes[player].iter().flat_map(|l| l.iter()) {
+                        if let Ship::BaseShip(ref mut s) = self.ships[s] {
+                            s.upgrade(&upgrade.effect);
+                        }
+                    }
+                }
+            }
+        }
         {
             let x = self.size_x();
             let y = self.size_y();
             let mut i = 0;
             while i < self.projectiles.len() {
-                if self.projectiles[i].tick(x, y) {
+                if self.projectiles[i].tick(x, y, &self.lanes, &mut self.ships, &mut self.sections, observer) {
                     i += 1;
                 } else {
                     self.projectiles.swap_remove(i);
@@ -49,10 +194,10 @@ impl Game {
         let (l1, l2) = self.lanes.split_at_mut(1);
         let projectile_ref = &mut self.projectiles;
         for l in l1[0].iter_mut() {
-            l.tick(&mut l2[0], &mut |x| projectile_ref.push(x))
+            l.tick(&l2[0], &mut self.ships, &mut self.sections, &mut |x| projectile_ref.push(x), observer)
         }
         for l in l2[0].iter_mut() {
-            l.tick(&mut l1[0], &mut |x| projectile_ref.push(x))
+            l.tick(&l1[0], &mut self.ships, &mut self.sections, &mut |x| projectile_ref.push(x), observer)
         }
     }
     pub fn lane(&self, direction: usize) -> &[Lane] {
@@ -68,7 +213,7 @@ impl Game {
     pub fn draw<T: graphics::RenderTarget>(&self, target: &mut T) {
         for lvec in self.lanes.iter() {
             for l in lvec.iter() {
-                l.draw(target);
+                l.draw(target, &self.ships);
             }
         }
         for p in self.projectiles.iter() {
@@ -81,10 +226,54 @@ impl Game {
     pub fn lane_count(&self) -> usize {
         self.lanes[0].len()
     }
+    //summed over all sections
+    pub fn mothership_health(&self, player: usize) -> u32 {
+        self.sections[player].iter().sum()
+    }
+    pub fn section_health(&self, player: usize, section: usize) -> u32 {
+        self.sections[player][section]
+    }
+    pub fn sections_destroyed(&self, player: usize) -> usize {
+        self.sections[player].iter().filter(|&&h| h == 0).count()
+    }
+    //covers everything that can diverge between server and clients, compared to detect desyncs
+    pub fn state_hash(&self) -> u64 {
+        let mut h = StateHasher::new();
+        self.credits.hash(&mut h);
+        for sections in self.sections.iter() {
+            for health in sections.iter() {
+                health.hash(&mut h);
+            }
+        }
+        for lanes in self.lanes.iter() {
+            for l in lanes.iter() {
+                (l.stance(), l.len()).hash(&mut h);
+                for &s in l.iter() {
+                    let s = &self.ships[s];
+                    (s.pos_x(), s.pos_y(), s.health(), s.shield(), s.effects().len()).hash(&mut h);
+                    for e in s.effects() {
+                        e.hash_state(&mut h);
+                    }
+                }
+            }
+        }
+        for r in self.research.iter() {
+            (r.done(), r.current()).hash(&mut h);
+        }
+        self.projectiles.len().hash(&mut h);
+        for p in self.projectiles.iter() {
+            p.hash_state(&mut h);
+        }
+        h.finish()
+    }
     pub fn winner(&self) -> Option<usize> {
-        if self.mothership_health[0].get() == 0 {
+        let needed = match self.config.win_mode {
+            WinMode::SharedHealth => 1,
+            WinMode::Sections(n) => n,
+        };
+        if self.sections_destroyed(0) >= needed {
             Some(1)
-        } else if self.mothership_health[1].get() == 0 {
+        } else if self.sections_destroyed(1) >= needed {
             Some(0)
         } else {
             None
@@ -0,0 +1,17 @@
+use super::ship::ShipId;
+
+//notified of what happens in the simulation, e.g. for stats, sound or logging
+//players are the owners of the ships involved, observers can't change the simulation
+pub trait GameObserver {
+    //ship_type is the index of the builder the ship was built from
+    fn ship_spawned(&mut self, _player: usize, _lane: usize, _ship: ShipId, _ship_type: usize) {}
+    //damage is the health and shield lost, kind its damage type, hits on motherships are reported by mothership_hit
+    fn ship_damaged(&mut self, _player: usize, _ship: ShipId, _kind: &str, _damage: u32) {}
+    fn ship_destroyed(&mut self, _player: usize, _lane: usize, _ship: ShipId) {}
+    fn projectile_fired(&mut self, _player: usize, _lane: usize) {}
+    fn projectile_hit(&mut self, _player: usize, _ship: ShipId) {}
+    fn mothership_hit(&mut self, _player: usize, _lane: usize, _kind: &str, _damage: u32) {}
+}
+
+impl GameObserver for () {}
+
@@ -1,17 +1,36 @@
 use super::ship::*;
-use std::rc::{Weak, Rc};
-use std::cell::RefCell;
 use std::cmp::max;
+use std::hash::{Hash, Hasher};
+use std::iter::once;
+use game::math::vec_len;
+use game::{GameObserver, Lane};
 use graphics;
 
+//distance from the path of a projectile at which ships are hit
+const HIT_RADIUS: i64 = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Projectile {
-    target: Weak<RefCell<Ship>>,
+    #[serde(default)]
+    target: Option<ShipId>,
     pos_x: i32,
     pos_y: i32,
     v_x: i32,
     v_y: i32,
     dmg: Damage,
+    blast_radius: Option<i32>,
+    //dumb-fire projectiles fly straight and hit any enemy ship in their path instead of only their target
+    #[serde(default)]
+    dumb_fire: bool,
+    //number of ships the projectile passes through before it is used up
+    #[serde(default)]
+    pierce: u32,
+    #[serde(default)]
+    effects: Vec<StatusEffect>,
+    //direction and index of the lane the target was in when the projectile was fired or last hit something
+    target_lane: (usize, usize),
     #[cfg_attr(not(feature = "graphics"), allow(dead_code))]
+    #[cfg_attr(not(feature = "graphics"), serde(skip_serializing, skip_deserializing))]
     sprite: graphics::Sprite,
 }
 
@@ -23,6 +42,11 @@ fn cross_p_scalar(v1: &[i64; 2], v2: &[i64; 2]) -> i64 {
     v1[0] * v2[1] - v1[1] * v2[0]
 }
 
+//the lane a ship is in now, ships keep their id when they change lanes
+fn lane_of(lanes: &[Lane], ship: ShipId) -> Option<&Lane> {
+    lanes.iter().find(|l| l.mothership() == ship || l.iter().any(|&s| s == ship))
+}
+
 fn collides(target: &[i64; 2], path: &[i64; 2], radius: i64) -> bool {
     let move_len_sq = path[0] * path[0] + path[1] * path[1];
     //area of parallelogram defined by points: start, end, target
@@ -32,35 +56,74 @@ fn collides(target: &[i64; 2], path: &[i64; 2], radius: i64) -> bool {
 }
 
 impl Projectile {
-    pub fn new(target: Rc<RefCell<Ship>>, x: i32, y: i32, v: i32, dmg: Damage, sprite: graphics::Sprite) -> Self {
-        let weak = Rc::downgrade(&target);
-        let target = target.borrow();
-        let dx = target.pos_x() - x;
-        let dy = target.pos_y() - y;
-        let hyp = max((dx as f64).hypot(dy as f64) as i32, 1);
+    pub fn new(target: ShipId,
+               (target_x, target_y): (i32, i32),
+               target_lane: &Lane,
+               x: i32,
+               y: i32,
+               v: i32,
+               dmg: Damage,
+               blast_radius: Option<i32>,
+               dumb_fire: bool,
+               pierce: u32,
+               sprite: graphics::Sprite)
+               -> Self {
+        let dx = target_x - x;
+        let dy = target_y - y;
+        let hyp = max(vec_len(dx, dy), 1);
         Projectile {
-            target: weak,
+            target: if dumb_fire { None } else { Some(target) },
             pos_x: x,
             pos_y: y,
             v_x: dx * v / hyp,
             v_y: dy * v / hyp,
             dmg: dmg,
+            blast_radius: blast_radius,
+            dumb_fire: dumb_fire,
+            pierce: pierce,
+            effects: Vec::new(),
+            target_lane: (target_lane.direction(), target_lane.index()),
             sprite: sprite,
         }
     }
-    pub fn tick(&mut self, game_size_x: i32, game_size_y: i32) -> bool {
+    //status effects applied to ships hit directly
+    pub fn with_effects(mut self, effects: Vec<StatusEffect>) -> Self {
+        self.effects = effects;
+        self
+    }
+    fn damage<O: GameObserver>(&self, ship: ShipId, lane: &Lane, ships: &mut ShipArena, sections: &mut [Vec<u32>; 2], observer: &mut O) {
+        observer.projectile_hit(lane.direction(), ship);
+        ships.deal_damage(ship, lane, &self.dmg, sections, observer);
+        for e in self.effects.iter() {
+            ships[ship].apply_effect(e);
+        }
+    }
+    pub fn tick<O: GameObserver>(&mut self, game_size_x: i32, game_size_y: i32, lanes: &[Vec<Lane>; 2], ships: &mut ShipArena,
+                                 sections: &mut [Vec<u32>; 2], observer: &mut O) -> bool {
+        let from = (self.pos_x, self.pos_y);
         self.pos_x += self.v_x;
         self.pos_y += self.v_y;
-        if let Some(target) = Weak::upgrade(&self.target) {
-            let mut target = target.borrow_mut();
-            let dx = target.pos_x() - self.pos_x;
-            let dy = target.pos_y() - self.pos_y;
+        if self.dumb_fire {
+            self.tick_dumb_fire(from, lanes, ships, sections, observer)
+                && self.pos_x >= 0 && self.pos_y >= 0 && self.pos_x < game_size_x && self.pos_y < game_size_y
+        } else if let Some((target, dx, dy)) = self.target.and_then(|id| ships.get(id).map(|t| (id, t.pos_x() - self.pos_x, t.pos_y() - self.pos_y))) {
             if dot_p(&[dx, dy], &[self.v_x, self.v_y]) < 0 {
-                if collides(&[dx as i64, dy as i64], &[-self.v_x as i64, -self.v_y as i64], 20) {
-                    target.apply_damage(&self.dmg);
-                    false
+                if collides(&[dx as i64, dy as i64], &[-self.v_x as i64, -self.v_y as i64], HIT_RADIUS) {
+                    let lane = lane_of(&lanes[self.target_lane.0], target).expect("ships in the arena are in a lane");
+                    self.target_lane = (lane.direction(), lane.index());
+                    self.damage(target, lane, ships, sections, observer);
+                    self.hit(target, lane, ships, sections, observer);
+                    //a piercing shot keeps flying and hits whatever comes next
+                    if self.pierce > 0 {
+                        self.pierce -= 1;
+                        self.target = None;
+                        self.dumb_fire = true;
+                        true
+                    } else {
+                        false
+                    }
                 } else {
-                    self.target = Weak::new();
+                    self.target = None;
                     true
                 }
             } else {
@@ -70,6 +133,63 @@ impl Projectile {
             self.pos_x >= 0 && self.pos_y >= 0 && self.pos_x < game_size_x && self.pos_y < game_size_y
         }
     }
+    //checks the enemy ships crossed by the path from `from` to the current position in the order they are reached
+    fn tick_dumb_fire<O: GameObserver>(&mut self, from: (i32, i32), lanes: &[Vec<Lane>; 2], ships: &mut ShipArena,
+                                       sections: &mut [Vec<u32>; 2], observer: &mut O) -> bool {
+        let v = [self.v_x, self.v_y];
+        let mut crossed = Vec::new();
+        for l in lanes[self.target_lane.0].iter() {
+            for &s in l.iter().chain(once(&l.mothership())) {
+                let health = match ships[s] {
+                    Ship::Mothership(ref m) => m.section_health(sections),
+                    Ship::BaseShip(ref b) => b.health(),
+                };
+                let (x, y) = (ships[s].pos_x(), ships[s].pos_y());
+                let before = dot_p(&[x - from.0, y - from.1], &v);
+                let after = [x - self.pos_x, y - self.pos_y];
+                if health > 0 && before > 0 && dot_p(&after, &v) <= 0
+                    && collides(&[after[0] as i64, after[1] as i64], &[-v[0] as i64, -v[1] as i64], HIT_RADIUS) {
+                    crossed.push((before, l, s));
+                }
+            }
+        }
+        crossed.sort_by_key(|&(before, _, _)| before);
+        for (_, l, s) in crossed {
+            self.damage(s, l, ships, sections, observer);
+            self.target_lane = (l.direction(), l.index());
+            self.hit(s, l, ships, sections, observer);
+            if self.pierce == 0 {
+                return false;
+            }
+            self.pierce -= 1;
+        }
+        true
+    }
+    fn hit<O: GameObserver>(&self, target: ShipId, lane: &Lane, ships: &mut ShipArena, sections: &mut [Vec<u32>; 2], observer: &mut O) {
+        if let Some(radius) = self.blast_radius {
+            let center = (ships[target].pos_x(), ships[target].pos_y());
+            self.explode(target, center, radius, lane, ships, sections, observer);
+        }
+    }
+    //the direct hit takes full damage, other ships in the lane take damage falling off linearly with distance
+    fn explode<O: GameObserver>(&self, target: ShipId, center: (i32, i32), radius: i32, lane: &Lane, ships: &mut ShipArena,
+                                sections: &mut [Vec<u32>; 2], observer: &mut O) {
+        for &ship in lane.iter() {
+            if ship == target {
+                continue;
+            }
+            let dist = vec_len(ships[ship].pos_x() - center.0, ships[ship].pos_y() - center.1);
+            if dist < radius {
+                ships.deal_damage(ship, lane, &self.dmg.scaled((radius - dist) as u32, radius as u32), sections, observer);
+            }
+        }
+    }
+    pub fn target_id(&self) -> Option<ShipId> {
+        self.target
+    }
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        (self.pos_x, self.pos_y, self.v_x, self.v_y, self.dumb_fire, self.pierce).hash(h);
+    }
     #[cfg(feature = "graphics")]
     pub fn draw<T: graphics::RenderTarget>(&self, rt: &mut T) {
         use graphics::TransformRender;
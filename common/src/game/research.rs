@@ -0,0 +1,58 @@
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum UpgradeEffect {
+    //damage of weapons dealing this damage type is increased by percent
+    WeaponDamage { kind: String, percent: u32 },
+    //max speed of ships built from this builder index is increased by percent
+    MaxSpeed { ship_type: usize, percent: u32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Upgrade {
+    pub cost: u32,
+    //ticks until the upgrade is done
+    pub research_time: u32,
+    pub effect: UpgradeEffect,
+    //also applied to ships already in the game when the research is done
+    #[serde(default)]
+    pub upgrade_existing: bool,
+}
+
+//research state of a player, upgrades are indices into GameConfig::upgrades
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Research {
+    done: Vec<usize>,
+    //upgrade being researched and ticks left
+    current: Option<(usize, u32)>,
+}
+
+impl Research {
+    pub fn done(&self) -> &[usize] {
+        &self.done
+    }
+    pub fn current(&self) -> Option<(usize, u32)> {
+        self.current
+    }
+    //only one upgrade is researched at a time and every upgrade only once
+    pub fn can_start(&self, upgrade: usize) -> bool {
+        self.current.is_none() && !self.done.contains(&upgrade)
+    }
+    pub fn start(&mut self, upgrade: usize, time: u32) {
+        self.current = Some((upgrade, time));
+    }
+    //returns the upgrade finished this tick
+    pub fn tick(&mut self) -> Option<usize> {
+        let finished = match self.current {
+            Some((upgrade, ref mut left)) => {
+                *left = left.saturating_sub(1);
+                if *left > 0 {
+                    return None;
+                }
+                upgrade
+            }
+            None => return None,
+        };
+        self.current = None;
+        self.done.push(finished);
+        Some(finished)
+    }
+}
@@ -0,0 +1,43 @@
+use super::StatusEffect;
+
+//triggered by the player for all ships of a type in a lane
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Ability {
+    cooldown: u32,
+    //applied to the ship itself, e.g. haste for an afterburner or overcharge for its weapons
+    #[serde(default)]
+    effects: Vec<StatusEffect>,
+    //fully recharges the ship's shield
+    #[serde(default)]
+    recharge_shield: bool,
+    //ticks until the ability can be used again
+    #[serde(default)]
+    ready_in: u32,
+}
+
+impl Ability {
+    pub fn ready(&self) -> bool {
+        self.ready_in == 0
+    }
+    pub fn ready_in(&self) -> u32 {
+        self.ready_in
+    }
+    pub fn effects(&self) -> &[StatusEffect] {
+        &self.effects
+    }
+    pub fn recharge_shield(&self) -> bool {
+        self.recharge_shield
+    }
+    //starts the cooldown, returns false if the ability is not ready
+    pub fn trigger(&mut self) -> bool {
+        if self.ready() {
+            self.ready_in = self.cooldown;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn tick(&mut self) {
+        self.ready_in = self.ready_in.saturating_sub(1);
+    }
+}
@@ -0,0 +1,103 @@
+use std::ops::{Index, IndexMut};
+use game::{GameObserver, Lane};
+use game::projectile::Projectile;
+use super::{Damage, Ship, ShipTrait};
+
+//handle of a ship in a ShipArena, ids of removed ships never refer to ships added later
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShipId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Slot {
+    generation: u32,
+    //None if the slot is free or the ship is taken out while it ticks
+    ship: Option<Ship>,
+}
+
+//owns all ships of a game, ships refer to each other by id
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ShipArena {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl ShipArena {
+    pub fn insert(&mut self, mut ship: Ship) -> ShipId {
+        let id = match self.free.pop() {
+            Some(index) => ShipId { index: index, generation: self.slots[index as usize].generation },
+            None => {
+                self.slots.push(Slot { generation: 0, ship: None });
+                ShipId { index: self.slots.len() as u32 - 1, generation: 0 }
+            }
+        };
+        ship.set_id(id);
+        self.slots[id.index as usize].ship = Some(ship);
+        id
+    }
+    pub fn remove(&mut self, id: ShipId) -> Option<Ship> {
+        let ship = self.slot_mut(id).and_then(|s| s.ship.take());
+        if ship.is_some() {
+            self.slots[id.index as usize].generation += 1;
+            self.free.push(id.index);
+        }
+        ship
+    }
+    fn slot_mut(&mut self, id: ShipId) -> Option<&mut Slot> {
+        self.slots.get_mut(id.index as usize).filter(|s| s.generation == id.generation)
+    }
+    //None for removed ships and for the ship that is currently ticking
+    pub fn get(&self, id: ShipId) -> Option<&Ship> {
+        self.slots.get(id.index as usize).filter(|s| s.generation == id.generation).and_then(|s| s.ship.as_ref())
+    }
+    pub fn get_mut(&mut self, id: ShipId) -> Option<&mut Ship> {
+        self.slot_mut(id).and_then(|s| s.ship.as_mut())
+    }
+    //the ship is taken out of the arena while it ticks, so it can access all other ships
+    pub fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, id: ShipId, own: &Lane, others: &[Lane], sections: &mut [Vec<u32>; 2],
+                                                       push_projectile: &mut F, observer: &mut O) {
+        let mut ship = match self.slot_mut(id).and_then(|s| s.ship.take()) {
+            Some(ship) => ship,
+            None => return,
+        };
+        ship.tick(own, others, self, sections, push_projectile, observer);
+        self.slots[id.index as usize].ship = Some(ship);
+    }
+    //applies damage to a ship in lane and reports it, motherships lose the health of their section
+    pub fn deal_damage<O: GameObserver>(&mut self, id: ShipId, lane: &Lane, dmg: &Damage, sections: &mut [Vec<u32>; 2],
+                                        observer: &mut O) {
+        match self[id] {
+            Ship::Mothership(ref m) => {
+                let health = &mut sections[m.player()][m.section()];
+                let lost = m.calc_damage(dmg).min(*health);
+                *health -= lost;
+                if lost > 0 {
+                    observer.mothership_hit(lane.direction(), lane.index(), &dmg.kind, lost);
+                }
+            }
+            Ship::BaseShip(ref mut s) => {
+                let before = s.health().saturating_add(s.shield());
+                s.apply_damage(dmg);
+                let lost = before.saturating_sub(s.health().saturating_add(s.shield()));
+                if lost > 0 {
+                    observer.ship_damaged(lane.direction(), id, &dmg.kind, lost);
+                }
+            }
+        }
+    }
+}
+
+impl Index<ShipId> for ShipArena {
+    type Output = Ship;
+    fn index(&self, id: ShipId) -> &Ship {
+        self.get(id).expect("no ship with this id")
+    }
+}
+
+impl IndexMut<ShipId> for ShipArena {
+    fn index_mut(&mut self, id: ShipId) -> &mut Ship {
+        self.get_mut(id).expect("no ship with this id")
+    }
+}
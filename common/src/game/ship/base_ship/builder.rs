@@ -2,32 +2,51 @@ use super::*;
 
 use graphics;
 
-#[cfg_attr(feature = "graphics", derive(Serialize))]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BaseShipBuilder {
-    laser_dmg_mult: u32,
-    plasma_dmg_mult: u32,
+    cost: u32,
+    //share of the damage of each type the ship takes, 0 is immune and unlisted types deal full damage
+    #[serde(default)]
+    damage_multipliers: DamageMultipliers,
     accel: i32,
     max_speed: i32,
     max_health: u32,
+    #[serde(default)]
+    shield: Option<Shield>,
     weapons: Vec<Weapon>,
-    #[cfg_attr(not(feature = "graphics"), serde(skip_deserializing))]
+    #[serde(default)]
+    abilities: Vec<Ability>,
+    #[cfg_attr(not(feature = "graphics"), serde(skip_serializing, skip_deserializing))]
     texture: graphics::CompositeTexture,
 }
 
 impl BaseShipBuilder {
-    pub fn build(&self) -> BaseShip {
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+    pub fn abilities(&self) -> &[Ability] {
+        &self.abilities
+    }
+    //ship_type is the index of the builder in the player's ship list
+    pub fn build(&self, ship_type: usize) -> BaseShip {
         BaseShip {
-            target: Weak::new(),
+            id: ShipId::default(),
+            ship_type: ship_type,
+            spawn: 0,
+            target: None,
             pos: 0,
             pos_y: 0,
-            laser_dmg_mult: self.laser_dmg_mult,
-            plasma_dmg_mult: self.plasma_dmg_mult,
+            damage_multipliers: self.damage_multipliers.clone(),
             speed: 0,
             accel: self.accel,
             max_speed: self.max_speed,
             health: self.max_health,
             max_health: self.max_health,
+            shield: self.shield.as_ref().map(Shield::charged),
+            transit: None,
+            stance: Stance::Advance,
+            effects: StatusEffects::default(),
+            abilities: self.abilities.clone(),
             weapons: self.weapons.clone(),
             sprite: self.texture.clone(),
         }
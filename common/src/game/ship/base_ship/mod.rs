@@ -1,64 +1,230 @@
 pub mod builder;
 
-use std::rc::{Weak, Rc};
-use std::cell::RefCell;
 use game::ship::weapon::*;
-use game::ship::{Ship, ShipTrait, Damage};
-use game::{Lane, Projectile};
+use game::ship::shield::Shield;
+use game::ship::{Ship, ShipArena, ShipTrait, ShipId, Damage, DamageMultipliers, StatusEffect, StatusEffects, Ability};
+use game::{GameObserver, Lane, Projectile, UpgradeEffect};
+use game::lane::{LANE_CHANGE_TIME, SHIP_SPACING};
 use graphics;
 
-fn mul_frac(m1: u32, m2: u32) -> u32 {
-    use std::u32::MAX;
-    ((m1 as u64 * m2 as u64) / MAX as u64) as u32
+//movement order given by the player for all ships in a lane
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stance {
+    Advance,
+    Hold,
+    Retreat,
 }
 
+impl Default for Stance {
+    fn default() -> Self {
+        Stance::Advance
+    }
+}
+
+//ship moving between lanes, pos_y is interpolated from from_y to to_y
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Transit {
+    from_y: i32,
+    to_y: i32,
+    ticks_left: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct BaseShip {
-    target: Weak<RefCell<Ship>>,
+    id: ShipId,
+    //index of the builder the ship was built from
+    #[serde(default)]
+    ship_type: usize,
+    //number of ships spawned in the game before this one, orders ships queueing at the same position
+    #[serde(default)]
+    spawn: u64,
+    #[serde(default)]
+    target: Option<ShipId>,
     pos: i32,
     pos_y: i32,
-    laser_dmg_mult: u32,
-    plasma_dmg_mult: u32,
+    damage_multipliers: DamageMultipliers,
     speed: i32,
     accel: i32,
     max_speed: i32,
     health: u32,
     max_health: u32,
+    shield: Option<Shield>,
+    #[serde(default)]
+    transit: Option<Transit>,
+    #[serde(default)]
+    stance: Stance,
+    #[serde(default)]
+    effects: StatusEffects,
+    #[serde(default)]
+    abilities: Vec<Ability>,
     weapons: Vec<Weapon>,
     #[cfg_attr(not(feature = "graphics"), allow(dead_code))]
+    #[cfg_attr(not(feature = "graphics"), serde(skip_serializing, skip_deserializing))]
     sprite: graphics::CompositeTexture,
 }
 
 impl BaseShip {
-    fn get_target(&mut self, lane: &Lane) -> Rc<RefCell<Ship>> {
+    fn get_target(&mut self, lane: &Lane, ships: &ShipArena) -> ShipId {
         let mut new_target = lane.mothership();
-        let mut min_dist = (lane.mothership().borrow().pos_x() - self.pos).abs();
-        for ship in lane.iter() {
-            let dist = ship.borrow().pos_x() - self.pos;
+        let mut min_dist = (ships[lane.mothership()].pos_x() - self.pos).abs();
+        for &ship in lane.iter() {
+            let dist = ships[ship].pos_x() - self.pos;
             if dist.abs() < min_dist && dist.signum() == self.accel.signum() {
                 min_dist = dist.abs();
                 new_target = ship;
             }
         }
-        self.target = Rc::downgrade(new_target);
-        new_target.clone()
-    }
-    fn do_move(&mut self, m: bool) {
-        if m {
-            self.speed += self.accel;
-            if self.speed.abs() > self.max_speed {
-                self.speed = self.max_speed * self.speed.signum();
+        self.target = Some(new_target);
+        new_target
+    }
+    //furthest position ahead the ship may move to, measured along its direction of movement
+    //friendly ships at the same position queue up in spawn order, ships changing lanes or destroyed this tick don't block
+    fn block_limit(&self, own: &Lane, enemies: &Lane, ships: &ShipArena) -> Option<i32> {
+        let dir = self.accel.signum();
+        let pos = self.pos * dir;
+        //the ship itself is ticking, so it isn't in ships and is skipped
+        let blocks = |s: &Ship| !s.in_transit() && s.health() > 0;
+        //lanes only list motherships separately, so every friend is a base ship
+        let spawn = |s: &Ship| match *s {
+            Ship::BaseShip(ref b) => b.spawn,
+            Ship::Mothership(_) => 0,
+        };
+        let friends = own.iter().filter_map(|&s| ships.get(s))
+            .filter(|&s| blocks(s))
+            .map(|s| (s.pos_x() * dir, spawn(s) < self.spawn));
+        let enemies = enemies.iter().map(|&s| &ships[s])
+            .filter(|&s| blocks(s))
+            .map(|s| (s.pos_x() * dir, false));
+        friends.chain(enemies)
+            .filter(|&(p, first)| p > pos || (p == pos && first))
+            .map(|(p, _)| p - SHIP_SPACING)
+            .min()
+    }
+    fn do_move(&mut self, m: bool, lane_len: i32, limit: Option<i32>) {
+        let accel = match self.stance {
+            Stance::Advance if m => self.accel,
+            Stance::Retreat => -self.accel,
+            _ => 0,
+        };
+        if accel != 0 {
+            self.speed += accel;
+            let max_speed = self.effects.max_speed(self.max_speed);
+            if self.speed.abs() > max_speed {
+                self.speed = max_speed * self.speed.signum();
             }
         } else {
             self.speed /= 2;
         }
-        self.pos += self.speed;
+        let dir = self.accel.signum();
+        let mut pos = self.pos + self.speed;
+        if let Some(limit) = limit {
+            //blocked ships stop, but are never pushed back
+            if pos * dir > limit {
+                pos = limit.max(self.pos * dir) * dir;
+                self.speed = 0;
+            }
+        }
+        self.pos = pos;
+        //retreating ships stop at their own mothership
+        let home = if self.accel > 0 { 0 } else { lane_len };
+        if (self.pos - home) * self.accel.signum() < 0 {
+            self.pos = home;
+            self.speed = 0;
+        }
     }
     pub fn set_pos_x(&mut self, pos: i32) {
         self.pos = pos;
     }
+    pub fn set_id(&mut self, id: ShipId) {
+        self.id = id;
+    }
+    pub fn ship_type(&self) -> usize {
+        self.ship_type
+    }
+    pub fn set_spawn(&mut self, spawn: u64) {
+        self.spawn = spawn;
+    }
+    pub fn upgrade(&mut self, effect: &UpgradeEffect) {
+        match *effect {
+            UpgradeEffect::WeaponDamage { ref kind, percent } => for w in self.weapons.iter_mut() {
+                w.upgrade_damage(kind, percent);
+            },
+            UpgradeEffect::MaxSpeed { ship_type, percent } => if ship_type == self.ship_type {
+                self.max_speed = (self.max_speed as i64 * (100 + percent as i64) / 100) as i32;
+            },
+        }
+    }
+    pub fn abilities(&self) -> &[Ability] {
+        &self.abilities
+    }
+    //returns false if the ship doesn't have the ability or it is on cooldown
+    pub fn use_ability(&mut self, ability: usize) -> bool {
+        let a = match self.abilities.get_mut(ability) {
+            Some(a) => a,
+            None => return false,
+        };
+        if !a.trigger() {
+            return false;
+        }
+        for e in a.effects() {
+            self.effects.apply(e);
+        }
+        if a.recharge_shield() {
+            if let Some(ref mut shield) = self.shield {
+                shield.recharge();
+            }
+        }
+        true
+    }
+    pub fn target_id(&self) -> Option<ShipId> {
+        self.target
+    }
+    pub fn stance(&self) -> Stance {
+        self.stance
+    }
+    pub fn set_stance(&mut self, stance: Stance) {
+        self.stance = stance;
+    }
+    //starts moving to another lane, the ship doesn't move or fire until it arrives
+    pub fn start_transit(&mut self, l: &Lane) {
+        let from_y = self.pos_y;
+        self.lane_changed(l);
+        self.transit = Some(Transit {
+            from_y: from_y,
+            to_y: self.pos_y,
+            ticks_left: LANE_CHANGE_TIME,
+        });
+        self.pos_y = from_y;
+    }
+    //a marker above the ship for every active effect
+    #[cfg(feature = "graphics")]
+    fn draw_effects<T: graphics::RenderTarget>(&self, rt: &mut T) {
+        for (i, e) in self.effects.as_slice().iter().enumerate() {
+            let x = i as f32 * 40.;
+            rt.draw_triangle(&[(x, -120.), (x + 30., -120.), (x + 15., -90.)], e.kind.color());
+        }
+    }
+    fn tick_transit(&mut self) -> bool {
+        let arrived = match self.transit {
+            Some(ref mut t) => {
+                t.ticks_left -= 1;
+                let left = t.ticks_left as i32;
+                self.pos_y = t.to_y + (t.from_y - t.to_y) * left / LANE_CHANGE_TIME as i32;
+                left == 0
+            }
+            None => return false,
+        };
+        if arrived {
+            self.transit = None;
+        }
+        true
+    }
 }
 
 impl ShipTrait for BaseShip {
+    fn id(&self) -> ShipId {
+        self.id
+    }
     fn pos_x(&self) -> i32 {
         self.pos
     }
@@ -66,35 +232,88 @@ impl ShipTrait for BaseShip {
         self.pos_y
     }
     fn calc_damage(&self, dmg: &Damage) -> u32 {
-        match *dmg {
-            Damage::Laser(power) => mul_frac(power, self.laser_dmg_mult),
-            Damage::Plasma(power) => mul_frac(power, self.plasma_dmg_mult),
-        }
+        self.damage_multipliers.apply(dmg)
     }
     fn apply_damage(&mut self, dmg: &Damage) {
-        self.health = self.health.saturating_sub(self.calc_damage(dmg));
+        let hull_dmg = if let Some(ref mut shield) = self.shield {
+            shield.absorb(dmg)
+        } else {
+            dmg.clone()
+        };
+        self.health = self.health.saturating_sub(self.calc_damage(&hull_dmg));
+    }
+    fn shield(&self) -> u32 {
+        self.shield.as_ref().map(Shield::charge).unwrap_or(0)
     }
     fn health(&self) -> u32 {
         self.health
     }
+    fn in_transit(&self) -> bool {
+        self.transit.is_some()
+    }
+    fn threat(&self) -> u32 {
+        self.weapons.iter().map(Weapon::threat).fold(0, u32::saturating_add)
+    }
+    fn apply_effect(&mut self, effect: &StatusEffect) {
+        self.effects.apply(effect);
+    }
+    fn effects(&self) -> &[StatusEffect] {
+        self.effects.as_slice()
+    }
+    fn repair(&mut self, amount: u32) {
+        self.health = self.health.saturating_add(amount).min(self.max_health);
+    }
     fn max_health(&self) -> u32 {
         self.max_health
     }
-    fn tick<F: FnMut(Projectile)>(&mut self, lane: usize, others: &[Lane], push_projectile: &mut F) {
-        let target_rc = self.get_target(&others[lane]);
-        let dist = (target_rc.borrow().pos_x() - self.pos).abs();
+    fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, own: &Lane, others: &[Lane], ships: &mut ShipArena,
+                                                sections: &mut [Vec<u32>; 2], push_projectile: &mut F, observer: &mut O) {
+        let lane = own.index();
+        if let Some(ref mut shield) = self.shield {
+            shield.tick();
+        }
+        for dmg in self.effects.tick() {
+            let before = self.health.saturating_add(self.shield());
+            self.apply_damage(&dmg);
+            let lost = before.saturating_sub(self.health.saturating_add(self.shield()));
+            if lost > 0 {
+                observer.ship_damaged(own.direction(), self.id, &dmg.kind, lost);
+            }
+        }
+        for a in self.abilities.iter_mut() {
+            a.tick();
+        }
+        if self.tick_transit() {
+            return;
+        }
+        let target_id = self.get_target(&others[lane], ships);
+        let dist = (ships[target_id].pos_x() - self.pos).abs();
         let mut target_args = TickArgs {
-            target: target_rc,
+            ship: self.id,
+            target: target_id,
+            target_lane: &others[lane],
+            own_lane: own,
             distance: dist,
             push_projectile: push_projectile,
             x: self.pos_x(),
             y: self.pos_y(),
+            overcharge: self.effects.overcharge(),
+            ships: ships,
+            sections: sections,
+            observer: observer,
         };
-        for w in self.weapons.iter_mut() {
+        let dir = self.accel.signum();
+        let stunned = self.effects.stunned();
+        for w in self.weapons.iter_mut().filter(|_| !stunned) {
+            let (target, target_lane, distance) = w.choose_target(target_id, &*target_args.ships, others, lane, self.pos, self.pos_y, dir);
+            target_args.target = target;
+            target_args.target_lane = target_lane;
+            target_args.distance = distance;
             w.tick(&mut target_args);
         }
         let move_control = self.weapons.iter().map(|x| x.control_move(dist)).sum::<i32>();
-        self.do_move(move_control >= 0);
+        let limit = self.block_limit(own, &others[lane], &*target_args.ships);
+        self.do_move(move_control >= 0, others[lane].distance(), limit);
     }
 
     fn lane_changed(&mut self, l: &Lane) {
@@ -104,14 +323,11 @@ impl ShipTrait for BaseShip {
         self.accel = if l.right_to_left() { -self.accel.abs() } else { self.accel.abs() };
     }
     #[cfg(feature = "graphics")]
-    fn draw<T: graphics::RenderTarget>(&self, rt: &mut T, lane: &Lane) {
+    fn draw<T: graphics::RenderTarget>(&self, rt: &mut T, _: &Lane) {
         use graphics::*;
         let pos_x = self.pos_x() as f32;
         let pos_y = self.pos_y() as f32;
-        let cell_rc = Weak::upgrade(&self.target).unwrap_or(lane.mothership().clone());
-        let cell_ref = cell_rc.borrow();
         let draw_args = DrawArgs {
-            target: &*cell_ref,
             parent: self,
         };
         if self.accel.signum() > 0 {
@@ -120,12 +336,14 @@ impl ShipTrait for BaseShip {
             for w in self.weapons.iter() {
                 w.draw(&mut render, &draw_args);
             }
+            self.draw_effects(&mut render);
         } else {
             let mut render = TransformRender::new(rt, move |(x, y)| (pos_x - x, pos_y + y));
             self.sprite.draw(&mut render);
             for w in self.weapons.iter() {
                 w.draw(&mut render, &draw_args);
             }
+            self.draw_effects(&mut render);
         };
 
     }
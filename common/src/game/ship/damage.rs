@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+use std::u32::MAX;
+use game::math::mul_frac;
+
+//damage types are plain names defined by ship data, e.g. "laser", "plasma" or "emp"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Damage {
+    pub kind: String,
+    pub power: u32,
+}
+
+impl Damage {
+    pub fn scaled(&self, num: u32, den: u32) -> Damage {
+        Damage {
+            kind: self.kind.clone(),
+            power: (self.power as u64 * num as u64 / den as u64) as u32,
+        }
+    }
+}
+
+//multipliers as fraction of u32::MAX per damage type, types not listed are applied at full power
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DamageMultipliers(BTreeMap<String, u32>);
+
+impl DamageMultipliers {
+    pub fn apply(&self, dmg: &Damage) -> u32 {
+        mul_frac(dmg.power, self.0.get(&dmg.kind).cloned().unwrap_or(MAX))
+    }
+}
@@ -1,28 +1,43 @@
 mod base_ship;
 mod weapon;
 mod mothership;
+mod shield;
+mod damage;
+mod status;
+mod ability;
+mod arena;
 
 use super::projectile::Projectile;
 use super::Lane;
+use super::GameObserver;
 #[cfg(feature = "graphics")]
 use graphics;
 
-pub use self::base_ship::BaseShip;
-pub use self::mothership::{Mothership, MOTHERSHIP_MAX_HEALTH};
+pub use self::base_ship::{BaseShip, Stance};
+pub use self::mothership::{Mothership, MothershipBuilder};
 pub use self::base_ship::builder::BaseShipBuilder;
-pub use self::weapon::Weapon;
+pub use self::weapon::{Weapon, TargetPolicy};
+pub use self::shield::Shield;
+pub use self::damage::{Damage, DamageMultipliers};
+pub use self::status::{StatusEffect, StatusEffects, EffectKind, Stacking};
+pub use self::ability::Ability;
+pub use self::arena::{ShipArena, ShipId};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
-pub enum Damage {
-    Laser(u32),
-    Plasma(u32),
-}
-
 pub enum Ship {
     BaseShip(BaseShip),
     Mothership(Mothership),
 }
 
+impl Ship {
+    pub fn set_id(&mut self, id: ShipId) {
+        match *self {
+            Ship::Mothership(ref mut m) => m.set_id(id),
+            Ship::BaseShip(ref mut s) => s.set_id(id),
+        }
+    }
+}
+
 macro_rules! impl_method {
     ($name:ident,$ret:ty,$($parname:ident : $partype:ty),*)=>{
         fn $name(&self,$($parname:$partype),*)->$ret{
@@ -45,17 +60,25 @@ macro_rules! impl_mut_method {
 }
 
 impl ShipTrait for Ship {
+    impl_method!(id,ShipId,);
     impl_method!(pos_x,i32,);
     impl_method!(pos_y,i32,);
     impl_method!(health,u32,);
     impl_method!(max_health,u32,);
+    impl_method!(shield,u32,);
+    impl_method!(in_transit,bool,);
+    impl_method!(threat,u32,);
     impl_method!(calc_damage,u32,dmg:&Damage);
     impl_mut_method!(apply_damage,(),dmg:&Damage);
+    impl_mut_method!(repair,(),amount:u32);
+    impl_mut_method!(apply_effect,(),effect:&StatusEffect);
+    impl_method!(effects,&[StatusEffect],);
     impl_mut_method!(lane_changed,(),l:&Lane);
-    fn tick<F: FnMut(Projectile)>(&mut self, lane: usize, others: &[Lane], push_projectile: &mut F) {
+    fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, own: &Lane, others: &[Lane], ships: &mut ShipArena,
+                                                   sections: &mut [Vec<u32>; 2], push_projectile: &mut F, observer: &mut O) {
         match *self {
-            Ship::Mothership(ref mut m) => m.tick(lane, others, push_projectile),
-            Ship::BaseShip(ref mut s) => s.tick(lane, others, push_projectile),
+            Ship::Mothership(ref mut m) => m.tick(own, others, ships, sections, push_projectile, observer),
+            Ship::BaseShip(ref mut s) => s.tick(own, others, ships, sections, push_projectile, observer),
         }
     }
     #[cfg(feature = "graphics")]
@@ -69,12 +92,31 @@ impl ShipTrait for Ship {
 
 
 pub trait ShipTrait {
+    fn id(&self) -> ShipId;
     fn pos_x(&self) -> i32;
     fn pos_y(&self) -> i32;
-    fn tick<F: FnMut(Projectile)>(&mut self, lane: usize, others: &[Lane], push_projectile: &mut F);
+    //own is the lane the ship is in, others are the enemy lanes, the ship itself is not in ships while it ticks
+    //sections holds the health of the mothership sections of both players
+    fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, own: &Lane, others: &[Lane], ships: &mut ShipArena,
+                                                   sections: &mut [Vec<u32>; 2], push_projectile: &mut F, observer: &mut O);
     fn lane_changed(&mut self, _: &Lane) {}
     fn health(&self) -> u32;
     fn max_health(&self) -> u32;
+    fn shield(&self) -> u32 {
+        0
+    }
+    fn in_transit(&self) -> bool {
+        false
+    }
+    fn repair(&mut self, _: u32) {}
+    fn apply_effect(&mut self, _: &StatusEffect) {}
+    fn effects(&self) -> &[StatusEffect] {
+        &[]
+    }
+    //used by weapons targeting the most dangerous ship
+    fn threat(&self) -> u32 {
+        0
+    }
     fn calc_damage(&self, dmg: &Damage) -> u32;
     fn apply_damage(&mut self, dmg: &Damage);
     #[cfg(feature = "graphics")]
@@ -1,29 +1,67 @@
 use super::*;
+use super::weapon::TickArgs;
 
-use std::cell::Cell;
-use std::rc::Rc;
 #[cfg(feature = "graphics")]
 use graphics;
 
-pub const MOTHERSHIP_MAX_HEALTH: u32 = 1_000_000;
+//turrets given to the mothership of every lane
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MothershipBuilder {
+    pub weapons: Vec<Weapon>,
+}
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Mothership {
+    id: ShipId,
     x: i32,
     y: i32,
-    health: Rc<Cell<u32>>
+    player: usize,
+    //motherships of the same player and section share the health stored for it in the game
+    section: usize,
+    target: Option<ShipId>,
+    weapons: Vec<Weapon>,
 }
 
 impl Mothership {
-    pub fn new(health: Rc<Cell<u32>>, x: i32, y: i32) -> Self {
+    pub fn new(player: usize, section: usize, x: i32, y: i32) -> Self {
         Mothership {
+            id: ShipId::default(),
             x: x,
             y: y,
-            health: health,
+            player: player,
+            section: section,
+            target: None,
+            weapons: Vec::new(),
         }
     }
+    pub fn set_id(&mut self, id: ShipId) {
+        self.id = id;
+    }
+    pub fn player(&self) -> usize {
+        self.player
+    }
+    pub fn section(&self) -> usize {
+        self.section
+    }
+    pub fn section_health(&self, sections: &[Vec<u32>; 2]) -> u32 {
+        sections[self.player][self.section]
+    }
+    pub fn weapons(&self) -> &[Weapon] {
+        &self.weapons
+    }
+    pub fn set_weapons(&mut self, weapons: Vec<Weapon>) {
+        self.weapons = weapons;
+    }
+    pub fn target_id(&self) -> Option<ShipId> {
+        self.target
+    }
 }
 
 impl ShipTrait for Mothership {
+    fn id(&self) -> ShipId {
+        self.id
+    }
+
     fn pos_x(&self) -> i32 {
         self.x
     }
@@ -32,28 +70,75 @@ impl ShipTrait for Mothership {
         self.y
     }
 
-    fn tick<F: FnMut(Projectile)>(&mut self, _: usize, _: &[Lane], _: &mut F) {}
+    fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, own: &Lane, others: &[Lane], ships: &mut ShipArena,
+                                                   sections: &mut [Vec<u32>; 2], push_projectile: &mut F, observer: &mut O) {
+        let lane = own.index();
+        //turrets of destroyed sections stay silent
+        if self.weapons.is_empty() || self.section_health(sections) == 0 {
+            return;
+        }
+        //the closest enemy ship in the lane that isn't changing lanes, each turret picks its own target from there
+        self.target = others[lane].iter()
+            .cloned()
+            .filter(|&s| !ships[s].in_transit())
+            .min_by_key(|&s| (ships[s].pos_x() - self.x).abs());
+        let mothership = others[lane].mothership();
+        let nearest = self.target.unwrap_or(mothership);
+        let dir = if self.player == 0 { 1 } else { -1 };
+        let mut target_args = TickArgs {
+            ship: self.id,
+            target: nearest,
+            target_lane: &others[lane],
+            own_lane: own,
+            distance: 0,
+            push_projectile: push_projectile,
+            x: self.x,
+            y: self.y,
+            overcharge: 0,
+            observer: observer,
+            ships: ships,
+            sections: sections,
+        };
+        for w in self.weapons.iter_mut() {
+            let (target, target_lane, distance) = w.choose_target(nearest, &*target_args.ships, others, lane, self.x, self.y, dir);
+            //turrets never shoot at the other mothership
+            if target == mothership {
+                continue;
+            }
+            target_args.target = target;
+            target_args.target_lane = target_lane;
+            target_args.distance = distance;
+            w.tick(&mut target_args);
+        }
+    }
 
+    //a mothership has no health of its own, see section_health
     fn health(&self) -> u32 {
-        self.health.get()
+        0
     }
 
     fn max_health(&self) -> u32 {
-        MOTHERSHIP_MAX_HEALTH
+        0
     }
 
     fn calc_damage(&self, dmg: &Damage) -> u32 {
-        match *dmg {
-            Damage::Laser(p) => p,
-            Damage::Plasma(p) => p,
-        }
+        dmg.power
     }
 
-    fn apply_damage(&mut self, dmg: &Damage) {
-        let new_health = self.health.get().saturating_sub(self.calc_damage(dmg));
-        self.health.set(new_health);
-    }
+    //damage to motherships is applied to their section by ShipArena::deal_damage
+    fn apply_damage(&mut self, _: &Damage) {}
 
     #[cfg(feature = "graphics")]
-    fn draw<T: graphics::RenderTarget>(&self, _: &mut T, _: &Lane) {}
+    fn draw<T: graphics::RenderTarget>(&self, rt: &mut T, _: &Lane) {
+        use graphics::*;
+        use super::weapon::DrawArgs;
+        let draw_args = DrawArgs {
+            parent: self,
+        };
+        let (x, y) = (self.x as f32, self.y as f32);
+        let mut render = TransformRender::new(rt, move |(px, py)| (x + px, y + py));
+        for w in self.weapons.iter() {
+            w.draw(&mut render, &draw_args);
+        }
+    }
 }
\ No newline at end of file
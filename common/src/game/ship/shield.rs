@@ -0,0 +1,103 @@
+use super::{Damage, DamageMultipliers};
+
+//absorbs damage before it reaches the hull and recharges when the ship has not been hit for a while
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Shield {
+    capacity: u32,
+    regen: u32,
+    //ticks after a hit until the shield starts recharging
+    regen_delay: u32,
+    #[serde(default)]
+    effectiveness: DamageMultipliers,
+    #[serde(default)]
+    charge: u32,
+    #[serde(default)]
+    delay: u32,
+}
+
+impl Shield {
+    pub fn charged(&self) -> Self {
+        Shield {
+            charge: self.capacity,
+            delay: 0,
+            ..self.clone()
+        }
+    }
+
+    pub fn recharge(&mut self) {
+        self.charge = self.capacity;
+        self.delay = 0;
+    }
+
+    pub fn charge(&self) -> u32 {
+        self.charge
+    }
+
+    //returns the part of dmg that passes through the shield
+    pub fn absorb(&mut self, dmg: &Damage) -> Damage {
+        let shield_dmg = self.effectiveness.apply(dmg);
+        if shield_dmg == 0 {
+            return dmg.clone();
+        }
+        self.delay = self.regen_delay;
+        if shield_dmg <= self.charge {
+            self.charge -= shield_dmg;
+            dmg.scaled(0, 1)
+        } else {
+            let passed = dmg.scaled(shield_dmg - self.charge, shield_dmg);
+            self.charge = 0;
+            passed
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if self.delay > 0 {
+            self.delay -= 1;
+        } else {
+            self.charge = self.capacity.min(self.charge.saturating_add(self.regen));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shield;
+    use game::ship::Damage;
+    use serde_json;
+
+    fn shield() -> Shield {
+        let json = r#"{"capacity":100,"regen":10,"regen_delay":2,"effectiveness":{"plasma":2147483647}}"#;
+        serde_json::from_str::<Shield>(json).unwrap().charged()
+    }
+
+    fn dmg(kind: &str, power: u32) -> Damage {
+        Damage { kind: kind.to_string(), power: power }
+    }
+
+    #[test]
+    fn test_absorb() {
+        let mut s = shield();
+        assert_eq!(s.absorb(&dmg("laser", 60)).power, 0);
+        assert_eq!(s.charge(), 40);
+        assert_eq!(s.absorb(&dmg("laser", 100)).power, 60);
+        assert_eq!(s.charge(), 0);
+        let mut s = shield();
+        assert_eq!(s.absorb(&dmg("plasma", 100)).power, 0);
+        assert_eq!(s.charge(), 51);
+    }
+
+    #[test]
+    fn test_regen() {
+        let mut s = shield();
+        s.absorb(&dmg("laser", 100));
+        s.tick();
+        s.tick();
+        assert_eq!(s.charge(), 0);
+        s.tick();
+        assert_eq!(s.charge(), 10);
+        for _ in 0..20 {
+            s.tick();
+        }
+        assert_eq!(s.charge(), 100);
+    }
+}
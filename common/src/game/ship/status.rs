@@ -0,0 +1,157 @@
+use std::mem;
+use std::hash::{Hash, Hasher};
+use std::u32::MAX;
+use game::math::mul_frac;
+use super::Damage;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum EffectKind {
+    //max speed is multiplied by this fraction of u32::MAX
+    Slow(u32),
+    //damage applied every tick
+    Burn(Damage),
+    //weapons don't fire
+    Stun,
+    //max speed is increased by this percentage
+    Haste(u32),
+    //weapon damage is increased by this percentage
+    Overcharge(u32),
+}
+
+impl EffectKind {
+    //fixed per variant, so state hashes match between machines
+    fn id(&self) -> u32 {
+        match *self {
+            EffectKind::Slow(_) => 0,
+            EffectKind::Burn(_) => 1,
+            EffectKind::Stun => 2,
+            EffectKind::Haste(_) => 3,
+            EffectKind::Overcharge(_) => 4,
+        }
+    }
+    #[cfg(feature = "graphics")]
+    pub fn color(&self) -> [u8; 4] {
+        match *self {
+            EffectKind::Slow(_) => [0, 128, 255, 255],
+            EffectKind::Burn(_) => [255, 128, 0, 255],
+            EffectKind::Stun => [255, 255, 0, 255],
+            EffectKind::Haste(_) => [0, 255, 128, 255],
+            EffectKind::Overcharge(_) => [255, 0, 255, 255],
+        }
+    }
+}
+
+//what happens when an effect is applied to a ship already affected by an effect of the same kind
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Stacking {
+    //the existing effect is replaced
+    Refresh,
+    //both effects are active
+    Stack,
+    //the new effect is dropped
+    Ignore,
+}
+
+impl Default for Stacking {
+    fn default() -> Self {
+        Stacking::Refresh
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    //remaining ticks for active effects
+    pub duration: u32,
+    #[serde(default)]
+    pub stacking: Stacking,
+}
+
+impl StatusEffect {
+    pub fn hash_state<H: Hasher>(&self, h: &mut H) {
+        (self.kind.id(), self.duration, self.stacking as u32).hash(h);
+    }
+}
+
+//the effects active on a ship
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: &StatusEffect) {
+        let existing = self.0.iter().position(|e| mem::discriminant(&e.kind) == mem::discriminant(&effect.kind));
+        match (existing, effect.stacking) {
+            (Some(i), Stacking::Refresh) => self.0[i] = effect.clone(),
+            (Some(_), Stacking::Ignore) => {}
+            _ => self.0.push(effect.clone()),
+        }
+    }
+    //returns the damage over time to apply this tick and removes expired effects
+    pub fn tick(&mut self) -> Vec<Damage> {
+        let burns = self.0.iter().filter_map(|e| match e.kind {
+            EffectKind::Burn(ref dmg) => Some(dmg.clone()),
+            _ => None,
+        }).collect();
+        for e in self.0.iter_mut() {
+            e.duration = e.duration.saturating_sub(1);
+        }
+        self.0.retain(|e| e.duration > 0);
+        burns
+    }
+    pub fn stunned(&self) -> bool {
+        self.0.iter().any(|e| e.kind == EffectKind::Stun)
+    }
+    pub fn max_speed(&self, max_speed: i32) -> i32 {
+        let factor = self.0.iter().fold(MAX, |f, e| match e.kind {
+            EffectKind::Slow(slow) => mul_frac(f, slow),
+            _ => f,
+        });
+        let haste = self.0.iter().fold(100u64, |h, e| match e.kind {
+            EffectKind::Haste(pct) => h + pct as u64,
+            _ => h,
+        });
+        (mul_frac(max_speed as u32, factor) as u64 * haste / 100) as i32
+    }
+    //extra weapon damage in percent
+    pub fn overcharge(&self) -> u32 {
+        self.0.iter().map(|e| match e.kind {
+            EffectKind::Overcharge(pct) => pct,
+            _ => 0,
+        }).fold(0, u32::saturating_add)
+    }
+    pub fn as_slice(&self) -> &[StatusEffect] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow(duration: u32, stacking: Stacking) -> StatusEffect {
+        StatusEffect {
+            kind: EffectKind::Slow(MAX / 2),
+            duration: duration,
+            stacking: stacking,
+        }
+    }
+
+    #[test]
+    fn stacking() {
+        let mut e = StatusEffects::default();
+        e.apply(&slow(10, Stacking::Refresh));
+        e.apply(&slow(20, Stacking::Refresh));
+        assert_eq!(e.as_slice().len(), 1);
+        assert_eq!(e.as_slice()[0].duration, 20);
+        e.apply(&slow(5, Stacking::Ignore));
+        assert_eq!(e.as_slice()[0].duration, 20);
+        assert_eq!(e.max_speed(100), 49);
+        e.apply(&slow(5, Stacking::Stack));
+        assert_eq!(e.max_speed(100), 24);
+        for _ in 0..5 {
+            e.tick();
+        }
+        assert_eq!(e.as_slice().len(), 1);
+        assert!(!e.stunned());
+    }
+}
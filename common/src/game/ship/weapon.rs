@@ -1,50 +1,103 @@
 use super::*;
-use std::rc::Rc;
-use std::cell::RefCell;
+use std::cmp::Reverse;
+use game::math::vec_len;
 use graphics;
 
 #[cfg(feature = "graphics")]
-pub struct DrawArgs<'a, 'b> {
-    pub target: &'a Ship,
-    pub parent: &'b BaseShip,
+pub struct DrawArgs<'a, S: ShipTrait + 'a> {
+    pub parent: &'a S,
 }
 
-pub struct TickArgs<F: FnMut(Projectile)> {
-    pub target: Rc<RefCell<Ship>>,
+pub struct TickArgs<'a, F: FnMut(Projectile), O: GameObserver + 'a> {
+    //the ship firing
+    pub ship: ShipId,
+    pub target: ShipId,
+    pub target_lane: &'a Lane,
+    //friendly ships, including the ship firing
+    pub own_lane: &'a Lane,
     pub distance: i32,
     pub push_projectile: F,
     pub x: i32,
     pub y: i32,
+    //extra damage in percent
+    pub overcharge: u32,
+    pub observer: &'a mut O,
+    pub ships: &'a mut ShipArena,
+    pub sections: &'a mut [Vec<u32>; 2],
 }
 
-#[cfg_attr(feature = "graphics", derive(Serialize))]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 enum WeaponClass {
     Laser {
         #[cfg(feature = "graphics")]
         color: [u8; 3],
-        power: u32,
+        dmg: Damage,
     },
     Launcher {
         dmg: Damage,
         speed: i32,
-        #[cfg_attr(not(feature = "graphics"), serde(skip_deserializing))]
+        #[cfg_attr(not(feature = "graphics"), serde(skip_serializing, skip_deserializing))]
         sprite: graphics::Sprite,
         cooldown: u32,
         launch_time: u32,
+        #[serde(default)]
+        blast_radius: Option<i32>,
+        #[serde(default)]
+        dumb_fire: bool,
+        #[serde(default)]
+        pierce: u32,
+    },
+    //heals the most damaged friendly ship in range every tick
+    Repair {
+        amount: u32,
+    },
+}
+
+//which enemy a weapon fires at, ships in range are preferred over the ship-wide nearest target
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TargetPolicy {
+    Nearest,
+    LowestHealth,
+    HighestMaxHealth,
+    MostDangerous,
+    Mothership,
+}
+
+impl Default for TargetPolicy {
+    fn default() -> Self {
+        TargetPolicy::Nearest
     }
 }
 
-#[cfg_attr(feature = "graphics", derive(Serialize))]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Weapon {
     range: i32,
     offset: (i32, i32),
     priority: i32,
+    #[serde(default)]
+    target_policy: TargetPolicy,
+    //number of adjacent lanes on each side the weapon can fire into
+    #[serde(default)]
+    lane_reach: usize,
+    //applied to every ship hit by the weapon or its projectiles
+    #[serde(default)]
+    effects: Vec<StatusEffect>,
+    //position of the last target, only used for drawing
+    #[serde(skip_serializing, skip_deserializing)]
+    target_pos: Option<(i32, i32)>,
     class: WeaponClass,
 }
 
 impl Weapon {
+    pub fn upgrade_damage(&mut self, kind: &str, percent: u32) {
+        match self.class {
+            WeaponClass::Laser { ref mut dmg, .. } | WeaponClass::Launcher { ref mut dmg, .. } => if dmg.kind == kind {
+                *dmg = dmg.scaled(100 + percent, 100);
+            },
+            WeaponClass::Repair { .. } => {}
+        }
+    }
+
     pub fn control_move(&self, distance: i32) -> i32 {
         if self.range() >= distance {
             -self.priority
@@ -53,20 +106,92 @@ impl Weapon {
         }
     }
 
-    pub fn tick<F: FnMut(Projectile)>(&mut self, args: &mut TickArgs<F>) {
+    //returns the target, its lane and the distance to it
+    //dir is the sign of the direction the ship moves in, only ships ahead of it are considered
+    pub fn choose_target<'a>(&self, nearest: ShipId, ships: &ShipArena, others: &'a [Lane], lane: usize, x: i32, y: i32, dir: i32)
+                             -> (ShipId, &'a Lane, i32) {
+        //without reach only the x distance matters, keeping targeting within a lane independent of pos_y
+        let reach = self.lane_reach;
+        let distance = |s: ShipId| {
+            let s = &ships[s];
+            if reach == 0 { (s.pos_x() - x).abs() } else { vec_len(s.pos_x() - x, s.pos_y() - y) }
+        };
+        let fallback = (nearest, &others[lane], distance(nearest));
+        let first = lane.saturating_sub(reach);
+        let last = (lane + reach).min(others.len() - 1);
+        let in_range = others[first..last + 1].iter()
+            .flat_map(|l| l.iter().map(move |&s| (s, l)))
+            .map(|(s, l)| (s, l, distance(s)))
+            .filter(|&(s, _, dist)| (ships[s].pos_x() - x).signum() == dir && dist <= self.range);
+        let best = match self.target_policy {
+            TargetPolicy::Nearest if reach == 0 => None,
+            //the ship-wide target wins ties, so other lanes are only preferred when strictly closer
+            TargetPolicy::Nearest => in_range.min_by_key(|&(_, _, dist)| dist).filter(|&(_, _, dist)| dist < fallback.2),
+            TargetPolicy::Mothership => {
+                let m = others[lane].mothership();
+                return (m, &others[lane], distance(m));
+            }
+            TargetPolicy::LowestHealth => in_range.min_by_key(|&(s, _, _)| ships[s].health()),
+            TargetPolicy::HighestMaxHealth => in_range.min_by_key(|&(s, _, _)| Reverse(ships[s].max_health())),
+            TargetPolicy::MostDangerous => in_range.min_by_key(|&(s, _, _)| Reverse(ships[s].threat())),
+        };
+        best.unwrap_or(fallback)
+    }
+
+    //damage dealt per 1000 ticks, ignoring damage multipliers
+    pub fn threat(&self) -> u32 {
         match self.class {
-            WeaponClass::Laser { power, .. } => if args.distance < self.range {
-                args.target.borrow_mut().apply_damage(&Damage::Laser(power))
+            WeaponClass::Laser { ref dmg, .. } => dmg.power.saturating_mul(1000),
+            WeaponClass::Launcher { ref dmg, cooldown, .. } => dmg.power.saturating_mul(1000) / cooldown.max(1),
+            WeaponClass::Repair { .. } => 0,
+        }
+    }
+
+    pub fn tick<F: FnMut(Projectile), O: GameObserver>(&mut self, args: &mut TickArgs<F, O>) {
+        let target_pos = {
+            let target = &args.ships[args.target];
+            (target.pos_x(), target.pos_y())
+        };
+        self.target_pos = Some(target_pos);
+        match self.class {
+            WeaponClass::Laser { ref dmg, .. } => if args.distance < self.range {
+                args.ships.deal_damage(args.target, args.target_lane, &dmg.scaled(100 + args.overcharge, 100), args.sections, args.observer);
+                let target = &mut args.ships[args.target];
+                for e in self.effects.iter() {
+                    target.apply_effect(e);
+                }
             },
-            WeaponClass::Launcher { ref dmg, ref speed, ref cooldown, ref mut launch_time, ref sprite } => {
+            WeaponClass::Launcher { ref dmg, ref speed, ref cooldown, ref mut launch_time, ref sprite, blast_radius, dumb_fire, pierce } => {
                 *launch_time = launch_time.saturating_sub(1);
                 if *launch_time == 0 && args.distance <= self.range {
                     let x = args.x + self.offset.0;
                     let y = args.y + self.offset.1;
-                    (args.push_projectile)(Projectile::new(args.target.clone(), x, y, *speed, dmg.clone(), sprite.clone()));
+                    (args.push_projectile)(Projectile::new(
+                        args.target, target_pos, args.target_lane, x, y, *speed, dmg.scaled(100 + args.overcharge, 100), blast_radius, dumb_fire, pierce, sprite.clone()
+                    ).with_effects(self.effects.clone()));
+                    args.observer.projectile_fired(args.own_lane.direction(), args.own_lane.index());
                     *launch_time = *cooldown;
                 }
             }
+            WeaponClass::Repair { amount } => {
+                let range = self.range;
+                let x = args.x;
+                let ship = args.ship;
+                //destroyed ships stay in the lane until the end of its tick and must not be revived
+                let target = {
+                    let ships = &args.ships;
+                    args.own_lane.iter()
+                        .filter(|&&s| s != ship)
+                        .filter_map(|&s| ships.get(s).filter(|b| b.health() > 0)
+                            .map(|b| (s, b.max_health() - b.health(), (b.pos_x() - x).abs())))
+                        .filter(|&(_, damage, dist)| damage > 0 && dist <= range)
+                        .min_by_key(|&(_, damage, _)| Reverse(damage))
+                        .map(|(s, _, _)| s)
+                };
+                if let Some(s) = target {
+                    args.ships[s].repair(amount);
+                }
+            }
         }
     }
 
@@ -75,21 +200,23 @@ impl Weapon {
     }
 
     #[cfg(feature = "graphics")]
-    pub fn draw<T: graphics::RenderTarget>(&self, rt: &mut T, draw: &DrawArgs) {
+    pub fn draw<T: graphics::RenderTarget, S: ShipTrait>(&self, rt: &mut T, draw: &DrawArgs<S>) {
         match self.class {
             WeaponClass::Laser { ref color, .. } => {
-                if (draw.target.pos_x() - draw.parent.pos_x()).abs() <= self.range {
-                    rt.draw_line(
-                        (self.offset.0 as f32, self.offset.1 as f32),
-                        (
-                            (draw.target.pos_x() - draw.parent.pos_x()) as f32,
-                            (draw.target.pos_y() - draw.parent.pos_y()) as f32
-                        ),
-                        [color[0], color[1], color[2], 255]
-                    );
+                if let Some((x, y)) = self.target_pos {
+                    if (x - draw.parent.pos_x()).abs() <= self.range {
+                        rt.draw_line(
+                            (self.offset.0 as f32, self.offset.1 as f32),
+                            (
+                                (x - draw.parent.pos_x()) as f32,
+                                (y - draw.parent.pos_y()) as f32
+                            ),
+                            [color[0], color[1], color[2], 255]
+                        );
+                    }
                 }
             },
-            WeaponClass::Launcher { .. } => {}
+            WeaponClass::Launcher { .. } | WeaponClass::Repair { .. } => {}
         }
     }
 }
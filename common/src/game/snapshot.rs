@@ -0,0 +1,47 @@
+use super::{Game, GameConfig, Lane, Research};
+use super::projectile::Projectile;
+use super::ship::ShipArena;
+
+//ships refer to each other by id, so the state of a running game is serialized as is
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameSnapshot {
+    pub config: GameConfig,
+    pub credits: [u32; 2],
+    pub ships: ShipArena,
+    pub lanes: [Vec<Lane>; 2],
+    pub sections: [Vec<u32>; 2],
+    #[serde(default)]
+    pub research: [Research; 2],
+    pub projectiles: Vec<Projectile>,
+    #[serde(default)]
+    pub spawned: u64,
+}
+
+impl Game {
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            config: self.config.clone(),
+            credits: self.credits,
+            ships: self.ships.clone(),
+            lanes: self.lanes.clone(),
+            sections: self.sections.clone(),
+            research: self.research.clone(),
+            projectiles: self.projectiles.clone(),
+            spawned: self.spawned,
+        }
+    }
+
+    pub fn restore(snapshot: GameSnapshot) -> Self {
+        let GameSnapshot { config, credits, ships, lanes, sections, research, projectiles, spawned } = snapshot;
+        Game {
+            config: config,
+            credits: credits,
+            ships: ships,
+            lanes: lanes,
+            sections: sections,
+            research: research,
+            projectiles: projectiles,
+            spawned: spawned,
+        }
+    }
+}
@@ -1,19 +1,27 @@
-use super::game::ship::BaseShipBuilder;
+use super::game::{Game, GameConfig, GameObserver};
+use super::game::ship::{BaseShipBuilder, MothershipBuilder, ShipId, Stance};
 
 mod buf_stream;
+mod replay;
+mod stats;
 
 pub use self::buf_stream::BufStream;
+pub use self::replay::Replay;
+pub use self::stats::{MatchStats, PlayerStats};
+
+//clients report Game::state_hash whenever the number of simulated ticks is a multiple of this
+pub const STATE_HASH_INTERVAL: usize = 64;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ClientJoin {
-    Create,
+    Create(GameConfig),
     Join(u32),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ServerJoin {
     Created(u32),
-    Start(usize),
+    Start(usize, GameConfig),
     JoinFail
 }
 
@@ -23,14 +31,23 @@ pub enum ServerJoin {
 //exchanged by clients through server after receiving ServerJoin::Start
 pub struct ClientStart {
     pub ships: Vec<BaseShipBuilder>,
+    #[serde(default)]
+    pub mothership: MothershipBuilder,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ClientGame {
     SpawnShip { id: usize, lane: usize },
+    MoveShip { ship: ShipId, lane: usize },
+    SetStance { lane: usize, stance: Stance },
+    //ship_type is the index of the ship in ClientStart::ships
+    UseAbility { lane: usize, ship_type: usize, ability: usize },
+    //upgrade is the index in GameConfig::upgrades
+    Research { upgrade: usize },
+    StateHash { tick: usize, hash: u64 },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ServerGameUpdate {
     pub tick: usize,
     pub events: Vec<(usize, ServerEvent)>
@@ -40,14 +57,103 @@ pub struct ServerGameUpdate {
 pub enum ServerGame {
     Update(ServerGameUpdate),
     OtherDisconnect,
-    End
+    Desync { tick: usize },
+    End(MatchStats),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ServerEvent {
     SpawnShip {
         player: usize,
         lane: usize,
         id: usize,
     },
+    MoveShip {
+        player: usize,
+        ship: ShipId,
+        lane: usize,
+    },
+    SetStance {
+        player: usize,
+        lane: usize,
+        stance: Stance,
+    },
+    UseAbility {
+        player: usize,
+        lane: usize,
+        ship_type: usize,
+        ability: usize,
+    },
+    Research {
+        player: usize,
+        upgrade: usize,
+    },
+}
+
+impl ServerEvent {
+    pub fn apply(&self, game: &mut Game, builders: &[Vec<BaseShipBuilder>; 2]) -> Result<(), &'static str> {
+        self.apply_observed(game, builders, &mut ())
+    }
+    pub fn apply_observed<O: GameObserver>(&self, game: &mut Game, builders: &[Vec<BaseShipBuilder>; 2], observer: &mut O)
+                                           -> Result<(), &'static str> {
+        match *self {
+            ServerEvent::SpawnShip { player, lane, id } => {
+                if player > 1 {
+                    return Err("invalid player in SpawnShip event");
+                }
+                if lane >= game.lane_count() {
+                    return Err("invalid lane in SpawnShip event");
+                }
+                if let Some(builder) = builders[player].get(id) {
+                    if !game.try_spend(player, builder.cost()) {
+                        return Err("unaffordable ship in SpawnShip event");
+                    }
+                    game.push_ship_observed(builder.build(id), player, lane, observer);
+                    Ok(())
+                } else {
+                    Err("invalid ship in SpawnShip event")
+                }
+            }
+            ServerEvent::MoveShip { player, ship, lane } => {
+                if player > 1 {
+                    return Err("invalid player in MoveShip event");
+                }
+                if game.move_ship(player, ship, lane) {
+                    Ok(())
+                } else {
+                    Err("impossible lane change in MoveShip event")
+                }
+            }
+            ServerEvent::SetStance { player, lane, stance } => {
+                if player > 1 {
+                    return Err("invalid player in SetStance event");
+                }
+                if game.set_stance(player, lane, stance) {
+                    Ok(())
+                } else {
+                    Err("invalid lane in SetStance event")
+                }
+            }
+            ServerEvent::UseAbility { player, lane, ship_type, ability } => {
+                if player > 1 {
+                    return Err("invalid player in UseAbility event");
+                }
+                if game.use_ability(player, lane, ship_type, ability) > 0 {
+                    Ok(())
+                } else {
+                    Err("no ship could use the ability in UseAbility event")
+                }
+            }
+            ServerEvent::Research { player, upgrade } => {
+                if player > 1 {
+                    return Err("invalid player in Research event");
+                }
+                if game.start_research(player, upgrade) {
+                    Ok(())
+                } else {
+                    Err("impossible research in Research event")
+                }
+            }
+        }
+    }
 }
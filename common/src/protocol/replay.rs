@@ -0,0 +1,100 @@
+use game::{Game, GameConfig};
+use game::ship::{BaseShipBuilder, MothershipBuilder};
+use super::ServerGameUpdate;
+
+//config, builders and the ordered event stream fully determine a match
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Replay {
+    pub config: GameConfig,
+    pub builders: [Vec<BaseShipBuilder>; 2],
+    #[serde(default)]
+    pub motherships: [MothershipBuilder; 2],
+    pub updates: Vec<ServerGameUpdate>,
+}
+
+impl Replay {
+    pub fn new(config: GameConfig, builders: [Vec<BaseShipBuilder>; 2], motherships: [MothershipBuilder; 2]) -> Self {
+        Replay {
+            config: config,
+            builders: builders,
+            motherships: motherships,
+            updates: Vec::new(),
+        }
+    }
+
+    pub fn end_tick(&self) -> usize {
+        self.updates.last().map(|u| u.tick).unwrap_or(0)
+    }
+
+    //re-simulates the match, on_tick is called after every tick with the number of ticks done
+    pub fn play<F: FnMut(usize, &Game)>(&self, mut on_tick: F) -> Result<Game, &'static str> {
+        if !self.config.is_valid() {
+            return Err("invalid game config");
+        }
+        let mut game = Game::new(&self.config);
+        game.set_mothership(0, &self.motherships[0]);
+        game.set_mothership(1, &self.motherships[1]);
+        let mut events = self.updates.iter().flat_map(|u| u.events.iter()).peekable();
+        for tick in 0..self.end_tick() {
+            while let Some(&&(evt_tick, ref evt)) = events.peek() {
+                if evt_tick > tick {
+                    break;
+                } else if evt_tick < tick {
+                    return Err("replay events out of order");
+                }
+                evt.apply(&mut game, &self.builders)?;
+                events.next();
+            }
+            game.tick();
+            on_tick(tick + 1, &game);
+        }
+        if events.next().is_some() {
+            Err("replay event after last update")
+        } else {
+            Ok(game)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Replay;
+    use game::GameConfig;
+    use protocol::{ServerEvent, ServerGameUpdate};
+    use serde_json;
+
+    const BUILDER_JSON: &'static str = r#"{"cost":100,"damage_multipliers":{"laser":0,"plasma":0},"accel":1,"max_speed":20,
+        "max_health":1000,"weapons":[]}"#;
+
+    fn replay(events: Vec<(usize, ServerEvent)>) -> Replay {
+        let mut replay = Replay::new(GameConfig::default(), [
+            vec![serde_json::from_str(BUILDER_JSON).unwrap()],
+            vec![serde_json::from_str(BUILDER_JSON).unwrap()]
+        ], Default::default());
+        replay.updates.push(ServerGameUpdate { tick: 16, events: events });
+        replay.updates.push(ServerGameUpdate { tick: 32, events: Vec::new() });
+        replay
+    }
+
+    #[test]
+    fn test_play() {
+        let replay = replay(vec![
+            (0, ServerEvent::SpawnShip { player: 0, lane: 1, id: 0 }),
+            (5, ServerEvent::SpawnShip { player: 1, lane: 0, id: 0 }),
+        ]);
+        let mut ticks = 0;
+        let game = replay.play(|tick, _| ticks = tick).unwrap();
+        assert_eq!(ticks, 32);
+        assert_eq!(game.lane(0)[1].len(), 1);
+        assert_eq!(game.lane(1)[0].len(), 1);
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let replay = replay(vec![
+            (5, ServerEvent::SpawnShip { player: 0, lane: 1, id: 0 }),
+            (0, ServerEvent::SpawnShip { player: 1, lane: 0, id: 0 }),
+        ]);
+        assert!(replay.play(|_, _| {}).is_err());
+    }
+}
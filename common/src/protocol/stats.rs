@@ -0,0 +1,81 @@
+use game::GameObserver;
+use game::ship::ShipId;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    //indexed by ship type
+    pub ships_spawned: Vec<u32>,
+    //enemy ships destroyed
+    pub kills: u32,
+    //damage dealt to enemy ships, by damage type
+    pub damage_dealt: Vec<(String, u64)>,
+    pub mothership_damage: u64,
+}
+
+//collected by the server during a match and sent with ServerGame::End
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MatchStats {
+    //in ticks
+    pub duration: usize,
+    pub players: [PlayerStats; 2],
+}
+
+impl GameObserver for MatchStats {
+    fn ship_spawned(&mut self, player: usize, _: usize, _: ShipId, ship_type: usize) {
+        let spawned = &mut self.players[player].ships_spawned;
+        if spawned.len() <= ship_type {
+            spawned.resize(ship_type + 1, 0);
+        }
+        spawned[ship_type] += 1;
+    }
+    fn ship_damaged(&mut self, player: usize, _: ShipId, kind: &str, damage: u32) {
+        let dealt = &mut self.players[player ^ 1].damage_dealt;
+        match dealt.iter().position(|&(ref k, _)| k == kind) {
+            Some(i) => dealt[i].1 += damage as u64,
+            None => dealt.push((kind.to_string(), damage as u64)),
+        }
+    }
+    fn ship_destroyed(&mut self, player: usize, _: usize, _: ShipId) {
+        self.players[player ^ 1].kills += 1;
+    }
+    fn mothership_hit(&mut self, player: usize, _: usize, _: &str, damage: u32) {
+        self.players[player ^ 1].mothership_damage += damage as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchStats;
+    use game::{Game, GameConfig, GameObserver};
+    use game::ship::ShipId;
+    use protocol::ServerEvent;
+    use serde_json;
+
+    const LASER_JSON: &'static str = r#"{"cost":0,"accel":1,"max_speed":20,"max_health":1000,"weapons":[
+        {"range":1000,"offset":[0,0],"priority":20,"class":{"Laser":{"color":[0,0,0],"dmg":{"kind":"laser","power":20}}}}]}"#;
+
+    #[test]
+    fn collect() {
+        let builders = [
+            vec![serde_json::from_str(LASER_JSON).unwrap(), serde_json::from_str(LASER_JSON).unwrap()],
+            Vec::new(),
+        ];
+        let mut game = Game::new(&GameConfig { lane_count: 1, lane_length: 1500, ..Default::default() });
+        let mut stats = MatchStats::default();
+        ServerEvent::SpawnShip { player: 0, lane: 0, id: 1 }.apply_observed(&mut game, &builders, &mut stats).unwrap();
+        for _ in 0..100 {
+            game.tick_observed(&mut stats);
+        }
+        assert_eq!(stats.players[0].ships_spawned, vec![0, 1]);
+        assert_eq!(stats.players[0].mothership_damage as u32, 1_000_000 - game.mothership_health(1));
+        assert!(stats.players[0].mothership_damage > 0);
+        let ship = ShipId::default();
+        stats.ship_damaged(1, ship, "laser", 10);
+        stats.ship_damaged(1, ship, "plasma", 20);
+        stats.ship_damaged(1, ship, "laser", 30);
+        stats.ship_destroyed(1, 0, ship);
+        assert_eq!(stats.players[0].damage_dealt, vec![("laser".to_string(), 40), ("plasma".to_string(), 20)]);
+        assert_eq!(stats.players[0].kills, 1);
+        assert_eq!(stats.players[1], Default::default());
+    }
+}
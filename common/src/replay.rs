@@ -0,0 +1,39 @@
+extern crate common;
+
+use std::env::args;
+use std::fs::File;
+use std::io::BufReader;
+use common::game::Game;
+use common::protocol::Replay;
+use common::serde_json;
+
+fn ship_count(game: &Game, player: usize) -> usize {
+    game.lane(player).iter().map(|l| l.len()).sum()
+}
+
+fn main() {
+    let path = args().nth(1).expect("usage: replay <replay file> [summary interval]");
+    let interval = args().nth(2).map(|s| s.parse().expect("parsing summary interval")).unwrap_or(1);
+    let file = File::open(&path).expect(&format!("cannot open {:?}", path));
+    let replay: Replay = serde_json::from_reader(BufReader::new(file)).expect("parsing replay");
+    let result = replay.play(|tick, game| {
+        if tick % interval == 0 {
+            println!("tick {}: ships {}/{}, credits {}/{}, mothership {}/{}, hash {:016x}",
+                     tick,
+                     ship_count(game, 0), ship_count(game, 1),
+                     game.credits(0), game.credits(1),
+                     game.mothership_health(0), game.mothership_health(1),
+                     game.state_hash());
+        }
+    });
+    match result {
+        Ok(game) => {
+            println!("ticks: {}", replay.end_tick());
+            match game.winner() {
+                Some(p) => println!("winner: player {}", p),
+                None => println!("winner: none"),
+            }
+        },
+        Err(e) => println!("invalid replay: {}", e),
+    }
+}
@@ -0,0 +1,224 @@
+extern crate common;
+
+use common::*;
+use common::game::{Game, GameConfig};
+use common::game::ship::{BaseShipBuilder, ShipTrait};
+
+const LASER_JSON: &'static str = r###"{
+        "cost":300,
+        "damage_multipliers":{"laser":2000000000,"plasma":2000000000},
+        "accel":1,
+        "max_speed":20,
+        "max_health":1000,
+        "weapons":[
+            {"range":1000,"offset":[0,0],"priority":20,"class":{"Laser":{"dmg":{"kind":"laser","power":20}}}}
+        ]
+    }"###;
+
+const PLASMA_JSON: &'static str = r###"{
+        "cost":400,
+        "damage_multipliers":{"laser":2000000000,"plasma":2000000000},
+        "accel":1,
+        "max_speed":20,
+        "max_health":1000,
+        "weapons":[
+            {"range":1000,"offset":[0,300],"priority":20,"class":{"Launcher":{
+                "dmg":{"kind":"plasma","power":500},"speed":100,"cooldown":60,"launch_time":0
+            }}}
+        ]
+    }"###;
+
+//Server and clients simulate independently, so a recorded event stream must always lead to the same state.
+//The expected states below were recorded from a run and have to be updated whenever gameplay is changed on purpose.
+
+//(tick, player, lane, ship)
+const EVENTS: [(usize, usize, usize, usize); 12] = [
+    (0, 0, 0, 0),
+    (0, 1, 0, 1),
+    (3, 0, 1, 1),
+    (40, 1, 1, 0),
+    (400, 0, 0, 1),
+    (410, 1, 0, 0),
+    (700, 0, 1, 0),
+    (900, 1, 1, 1),
+    (1200, 0, 0, 0),
+    (1200, 0, 0, 1),
+    (1500, 1, 0, 1),
+    (2000, 1, 1, 1),
+];
+
+//(tick, player, from lane, to lane), moves the front ship of a lane
+const MOVES: [(usize, usize, usize, usize); 4] = [
+    (420, 0, 0, 1),
+    (920, 1, 1, 0),
+    (1202, 0, 0, 1),
+    (1600, 0, 0, 1),
+];
+
+#[derive(Debug, PartialEq)]
+struct FinalState {
+    tick: usize,
+    winner: Option<usize>,
+    mothership_health: [u32; 2],
+    credits: [u32; 2],
+    ships: Vec<(usize, usize, i32, i32, u32)>,
+}
+
+fn config() -> GameConfig {
+    GameConfig {
+        lane_count: 2,
+        lane_length: 3_000,
+        ..GameConfig::default()
+    }
+}
+
+fn builders() -> Vec<BaseShipBuilder> {
+    vec![
+        serde_json::from_str(LASER_JSON).unwrap(),
+        serde_json::from_str(PLASMA_JSON).unwrap(),
+    ]
+}
+
+//applies EVENTS and moves and ticks g from start until end or until the game is won, returns the reached tick
+fn simulate(g: &mut Game, moves: &[(usize, usize, usize, usize)], start: usize, end: usize) -> usize {
+    let builders = builders();
+    let mut events = EVENTS.iter().filter(|e| e.0 >= start).peekable();
+    let mut tick = start;
+    while tick < end && g.winner().is_none() {
+        while let Some(&&(t, player, lane, ship)) = events.peek() {
+            if t != tick {
+                break;
+            }
+            if g.try_spend(player, builders[ship].cost()) {
+                g.push_ship(builders[ship].build(ship), player, lane);
+            }
+            events.next();
+        }
+        for &(_, player, from, to) in moves.iter().filter(|m| m.0 == tick) {
+            let s = g.front_ship(player, from).expect("no ship to move");
+            assert!(g.move_ship(player, s, to));
+        }
+        g.tick();
+        tick += 1;
+    }
+    tick
+}
+
+fn run(moves: &[(usize, usize, usize, usize)], max_ticks: usize) -> FinalState {
+    let mut g = Game::new(&config());
+    let tick = simulate(&mut g, moves, 0, max_ticks);
+    final_state(&g, tick)
+}
+
+fn final_state(g: &Game, tick: usize) -> FinalState {
+    let mut ships = Vec::new();
+    for direction in 0..2 {
+        for (lane_id, lane) in g.lane(direction).iter().enumerate() {
+            for &s in lane.iter() {
+                let s = &g.ships()[s];
+                ships.push((direction, lane_id, s.pos_x(), s.pos_y(), s.health()));
+            }
+        }
+    }
+    let mothership_health = [g.section_health(0, 0), g.section_health(1, 0)];
+    FinalState {
+        tick: tick,
+        winner: g.winner(),
+        mothership_health: mothership_health,
+        credits: [g.credits(0), g.credits(1)],
+        ships: ships,
+    }
+}
+
+#[test]
+fn recorded_mid_game() {
+    assert_eq!(run(&[], 600), FinalState {
+        tick: 600,
+        winner: None,
+        mothership_health: [993420, 995200],
+        credits: [500, 600],
+        ships: vec![(0, 0, 1728, 500, 55), (1, 0, 2692, 500, 536), (1, 1, 964, 1500, 536)],
+    });
+}
+
+#[test]
+fn recorded_full_game() {
+    assert_eq!(run(&[], 1_000_000), FinalState {
+        tick: 118813,
+        winner: Some(1),
+        mothership_health: [0, 8400],
+        credits: [117713, 117613],
+        ships: vec![(0, 0, 2014, 500, 72), (1, 1, 972, 1500, 1000), (1, 1, 1072, 1500, 1000)],
+    });
+}
+
+#[test]
+fn recorded_lane_changes() {
+    assert_eq!(run(&MOVES, 2_000), FinalState {
+        tick: 2000,
+        winner: None,
+        mothership_health: [976080, 955860],
+        credits: [900, 1200],
+        ships: vec![(0, 1, 2036, 1500, 214), (0, 1, 1936, 1500, 1000), (0, 1, 1836, 1500, 1000), (0, 1, 2020, 1500, 72),
+                    (1, 0, 972, 500, 1000)],
+    });
+    assert_eq!(run(&MOVES, 1_000_000), FinalState {
+        tick: 49907,
+        winner: Some(0),
+        mothership_health: [576580, 0],
+        credits: [48807, 48707],
+        ships: vec![(0, 1, 2004, 1500, 536), (0, 1, 1904, 1500, 1000), (1, 0, 972, 500, 1000)],
+    });
+}
+
+#[test]
+fn state_hash_detects_divergence() {
+    let builder: BaseShipBuilder = serde_json::from_str(LASER_JSON).unwrap();
+    let mut g1 = Game::new(&config());
+    let mut g2 = Game::new(&config());
+    for _ in 0..100 {
+        g1.tick();
+        g2.tick();
+    }
+    assert_eq!(g1.state_hash(), g2.state_hash());
+    assert!(g1.try_spend(0, builder.cost()));
+    g1.push_ship(builder.build(0), 0, 1);
+    assert!(g1.state_hash() != g2.state_hash());
+}
+
+#[test]
+fn state_hash_covers_effects() {
+    use common::game::ship::{EffectKind, Stacking, StatusEffect};
+    let builder: BaseShipBuilder = serde_json::from_str(LASER_JSON).unwrap();
+    let mut g1 = Game::new(&config());
+    let mut g2 = Game::new(&config());
+    let id = g1.push_ship(builder.build(0), 0, 0);
+    g2.push_ship(builder.build(0), 0, 0);
+    let slow = |duration| StatusEffect { kind: EffectKind::Slow(0), duration: duration, stacking: Stacking::Refresh };
+    g1.ship_mut(id).unwrap().apply_effect(&slow(10));
+    g2.ship_mut(id).unwrap().apply_effect(&slow(10));
+    assert_eq!(g1.state_hash(), g2.state_hash());
+    //same number of effects, but a different duration or kind
+    g2.ship_mut(id).unwrap().apply_effect(&slow(20));
+    assert!(g1.state_hash() != g2.state_hash());
+    g2.ship_mut(id).unwrap().apply_effect(&slow(10));
+    g1.ship_mut(id).unwrap().apply_effect(&StatusEffect { kind: EffectKind::Stun, duration: 10, stacking: Stacking::Refresh });
+    g2.ship_mut(id).unwrap().apply_effect(&StatusEffect { kind: EffectKind::Haste(0), duration: 10, stacking: Stacking::Refresh });
+    assert!(g1.state_hash() != g2.state_hash());
+}
+
+#[test]
+fn snapshot_resume() {
+    //the lane changes leave ships in transit when the snapshot is taken
+    for &(moves, snapshot_tick) in &[(&[][..], 600), (&MOVES[..], 1_220)] {
+        let mut g = Game::new(&config());
+        simulate(&mut g, moves, 0, snapshot_tick);
+        let json = serde_json::to_string(&g.snapshot()).unwrap();
+        let mut restored = Game::restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(g.state_hash(), restored.state_hash());
+        let tick = simulate(&mut g, moves, snapshot_tick, 1_000_000);
+        assert_eq!(simulate(&mut restored, moves, snapshot_tick, 1_000_000), tick);
+        assert_eq!(final_state(&g, tick), final_state(&restored, tick));
+        assert_eq!(g.state_hash(), restored.state_hash());
+    }
+}
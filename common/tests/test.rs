@@ -3,13 +3,13 @@ extern crate common;
 use common::*;
 
 const BUILDER_JSON: &'static str = r###"{
-        "laser_dmg_mult":2000000000,
-        "plasma_dmg_mult":2000000000,
+        "cost":100,
+        "damage_multipliers":{"laser":2000000000,"plasma":2000000000},
         "accel":1,
         "max_speed":20,
         "max_health":1000,
         "weapons":[
-            {"range":1000,"offset":[0,0],"priority":20,"class":{"Laser":{"color":[0,0,0],"power":20}}}
+            {"range":1000,"offset":[0,0],"priority":20,"class":{"Laser":{"color":[0,0,0],"dmg":{"kind":"laser","power":20}}}}
         ],
         "texture":{
             "parts":[
@@ -18,13 +18,504 @@ const BUILDER_JSON: &'static str = r###"{
         }
     }"###;
 
+fn config(lane_count: usize, lane_length: i32) -> game::GameConfig {
+    game::GameConfig {
+        lane_count: lane_count,
+        lane_length: lane_length,
+        ..Default::default()
+    }
+}
+
+fn game(lane_count: usize, lane_length: i32) -> game::Game {
+    game::Game::new(&config(lane_count, lane_length))
+}
+
+fn tick(g: &mut game::Game, ticks: usize) {
+    for _ in 0..ticks {
+        g.tick();
+    }
+}
+
+fn health(g: &game::Game, player: usize, lane: usize) -> Vec<u32> {
+    use game::ship::ShipTrait;
+    g.lane(player)[lane].iter().map(|&s| g.ships()[s].health()).collect()
+}
+
+//a slow unarmed ship with 1000 health, fields is a json object overriding builder fields
+fn builder(fields: &str) -> game::ship::BaseShipBuilder {
+    let mut json: serde_json::Value = serde_json::from_str(r###"{
+        "cost":0,
+        "accel":1,
+        "max_speed":5,
+        "max_health":1000,
+        "weapons":[]
+    }"###).unwrap();
+    let fields: serde_json::Value = serde_json::from_str(fields).unwrap();
+    json.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+    serde_json::from_value(json).unwrap()
+}
+
+fn armed(weapon: &str) -> game::ship::BaseShipBuilder {
+    builder(&format!(r###"{{"weapons":[{}]}}"###, weapon))
+}
+
+//fields holds the class and optional weapon fields
+fn weapon(range: i32, priority: i32, fields: &str) -> String {
+    format!(r###"{{"range":{},"offset":[0,0],"priority":{},{}}}"###, range, priority, fields)
+}
+
+fn laser(power: u32) -> String {
+    format!(r###""class":{{"Laser":{{"color":[0,0,0],"dmg":{{"kind":"laser","power":{}}}}}}}"###, power)
+}
+
+fn launcher(fields: &str) -> String {
+    format!(r###""class":{{"Launcher":{{"dmg":{{"kind":"plasma","power":1000}},"speed":100,{}}}}}"###, fields)
+}
+
+//a slow unarmed ship with 100000 health that takes full damage of every type
+fn target() -> game::ship::BaseShipBuilder {
+    builder(r###"{"max_health":100000}"###)
+}
+
+fn splash_launcher(blast_radius: &str) -> game::ship::BaseShipBuilder {
+    let launcher = launcher(&format!(r###""cooldown":1000,"launch_time":10,"blast_radius":{}"###, blast_radius));
+    builder(&format!(r###"{{"damage_multipliers":{{"laser":0,"plasma":0}},"max_speed":20,"weapons":[{}]}}"###, weapon(5000, 20, &launcher)))
+}
+
+fn sniper(policy: &str, lane_reach: usize) -> game::ship::BaseShipBuilder {
+    armed(&weapon(5000, 100, &format!(r###""target_policy":"{}","lane_reach":{},{}"###, policy, lane_reach, laser(10))))
+}
+
+fn repairer() -> game::ship::BaseShipBuilder {
+    armed(&weapon(500, 0, r###""class":{"Repair":{"amount":300}}"###))
+}
+
 #[test]
 fn create_push_tick() {
     let builder: game::ship::BaseShipBuilder = serde_json::from_str(BUILDER_JSON).unwrap();
-    let mut g = game::Game::new(6, 50);
-    g.push_ship(builder.build(), 0, 0);
-    g.push_ship(builder.build(), 1, 0);
-    for _ in 0..10000 {
-        g.tick();
+    let mut g = game(6, 50);
+    g.push_ship(builder.build(0), 0, 0);
+    g.push_ship(builder.build(0), 1, 0);
+    tick(&mut g, 10000);
+}
+
+fn splash_damage(blast_radius: &str) -> Vec<u32> {
+    let mut g = game(1, 3_000);
+    g.push_ship(splash_launcher(blast_radius).build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 0);
+    g.push_ship(target().build(0), 1, 0);
+    tick(&mut g, 60);
+    health(&g, 1, 0)
+}
+
+#[test]
+fn launcher_splash() {
+    assert_eq!(splash_damage("null"), vec![99000, 100000]);
+    //the second target queues behind the first and takes reduced splash damage
+    assert_eq!(splash_damage("300"), vec![99000, 99367]);
+}
+
+#[test]
+fn lane_change() {
+    use game::ship::ShipTrait;
+    let mut g = game(3, 3_000);
+    let id = g.push_ship(target().build(0), 0, 1);
+    g.tick();
+    assert!(!g.move_ship(0, id, 1));
+    assert!(!g.move_ship(1, id, 0));
+    assert!(g.move_ship(0, id, 0));
+    assert!(!g.move_ship(0, id, 1));
+    let pos_x = g.ships()[g.lane(0)[0][0]].pos_x();
+    g.tick();
+    {
+        let s = &g.ships()[g.lane(0)[0][0]];
+        assert!(s.in_transit());
+        assert_eq!(s.pos_x(), pos_x);
+        assert!(s.pos_y() > g.lane_y_range(0).1);
     }
-}
\ No newline at end of file
+    tick(&mut g, 100);
+    let s = &g.ships()[g.lane(0)[0][0]];
+    assert!(!s.in_transit());
+    assert!(s.pos_x() > pos_x);
+    assert_eq!(s.pos_y(), 500);
+    assert!(g.lane(0)[1].is_empty());
+}
+
+#[test]
+fn splash_follows_lane_change() {
+    let mut g = game(2, 3_000);
+    g.push_ship(splash_launcher("2000").build(0), 0, 0);
+    let id = g.push_ship(target().build(0), 1, 0);
+    g.push_ship(target().build(0), 1, 0);
+    g.push_ship(target().build(0), 1, 1);
+    //the shot is fired at the first target and lands right after it starts moving to the other lane
+    tick(&mut g, 37);
+    assert!(g.move_ship(1, id, 1));
+    tick(&mut g, 20);
+    assert_eq!(health(&g, 1, 0), vec![100000]);
+    assert_eq!(health(&g, 1, 1), vec![99490, 99000]);
+}
+
+#[test]
+fn stances() {
+    use game::ship::{ShipTrait, Stance};
+    let mut g = game(1, 3_000);
+    g.push_ship(target().build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 0);
+    tick(&mut g, 50);
+    let pos = |g: &game::Game, player: usize| g.ships()[g.lane(player)[0][0]].pos_x();
+    let advanced = [pos(&g, 0), pos(&g, 1)];
+    assert!(g.set_stance(0, 0, Stance::Hold));
+    assert!(g.set_stance(1, 0, Stance::Retreat));
+    assert!(!g.set_stance(1, 1, Stance::Retreat));
+    tick(&mut g, 10);
+    let held = pos(&g, 0);
+    assert!(held >= advanced[0]);
+    tick(&mut g, 100);
+    assert_eq!(pos(&g, 0), held);
+    assert_eq!(pos(&g, 1), 3_000);
+    g.push_ship(target().build(0), 1, 0);
+    assert_eq!(g.ships()[g.lane(1)[0][1]].pos_x(), 3_000);
+}
+
+#[test]
+fn mothership_turrets() {
+    let mothership: game::ship::MothershipBuilder =
+        serde_json::from_str(&format!(r###"{{"weapons":[{}]}}"###, weapon(1000, 0, &laser(2000)))).unwrap();
+    let mut g = game(2, 1_500);
+    g.set_mothership(1, &mothership);
+    g.push_ship(target().build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 1);
+    tick(&mut g, 2000);
+    assert!(g.lane(0)[0].is_empty());
+    assert_eq!(g.lane(1)[1].len(), 1);
+}
+
+fn turret_damage(policy: &str) -> Vec<u32> {
+    use game::ship::{Damage, ShipTrait};
+    let turret = weapon(5000, 0, &format!(r###""target_policy":"{}",{}"###, policy, laser(10)));
+    let mothership: game::ship::MothershipBuilder = serde_json::from_str(&format!(r###"{{"weapons":[{}]}}"###, turret)).unwrap();
+    let mut g = game(1, 3_000);
+    g.set_mothership(1, &mothership);
+    g.push_ship(target().build(0), 0, 0);
+    let id = g.push_ship(target().build(0), 0, 0);
+    g.ship_mut(id).unwrap().apply_damage(&Damage { kind: "laser".into(), power: 1000 });
+    tick(&mut g, 10);
+    health(&g, 0, 0)
+}
+
+#[test]
+fn turret_policies() {
+    assert_eq!(turret_damage("Nearest"), vec![99900, 99000]);
+    assert_eq!(turret_damage("LowestHealth"), vec![100000, 98900]);
+    //turrets never shoot at the other mothership
+    assert_eq!(turret_damage("Mothership"), vec![100000, 99000]);
+}
+
+#[test]
+fn mothership_sections() {
+    let builder: game::ship::BaseShipBuilder = serde_json::from_str(BUILDER_JSON).unwrap();
+    let run = |win_mode| {
+        let mut g = game::Game::new(&game::GameConfig {
+            mothership_health: 10_000,
+            win_mode: win_mode,
+            ..config(2, 1_500)
+        });
+        g.push_ship(builder.build(0), 0, 0);
+        let mut ticks = 0;
+        while g.winner().is_none() && ticks < 10_000 {
+            g.tick();
+            ticks += 1;
+        }
+        g
+    };
+    let g = run(game::WinMode::Sections(1));
+    assert_eq!(g.winner(), Some(0));
+    assert_eq!(g.sections_destroyed(1), 1);
+    assert_eq!(g.section_health(1, 0), 0);
+    assert_eq!(g.section_health(1, 1), 10_000);
+    assert_eq!(g.mothership_health(1), 10_000);
+    let g = run(game::WinMode::Sections(2));
+    assert_eq!(g.winner(), None);
+    let g = run(game::WinMode::SharedHealth);
+    assert_eq!(g.winner(), Some(0));
+    assert_eq!(g.section_health(1, 0), 0);
+    assert!(!game::GameConfig { win_mode: game::WinMode::Sections(5), ..Default::default() }.is_valid());
+}
+
+#[test]
+fn repair_weapon() {
+    use game::ship::{Damage, ShipTrait};
+    let mut g = game(1, 10_000);
+    let ids = [
+        g.push_ship(target().build(0), 0, 0),
+        g.push_ship(target().build(0), 0, 0),
+        g.push_ship(repairer().build(0), 0, 0),
+    ];
+    let damage = |power| Damage { kind: "laser".into(), power: power };
+    g.ship_mut(ids[0]).unwrap().apply_damage(&damage(1000));
+    g.ship_mut(ids[1]).unwrap().apply_damage(&damage(500));
+    g.ship_mut(ids[2]).unwrap().apply_damage(&damage(500));
+    g.tick();
+    assert_eq!(health(&g, 0, 0), vec![99300, 99500, 500]);
+    tick(&mut g, 10);
+    assert_eq!(health(&g, 0, 0), vec![100000, 100000, 500]);
+}
+
+#[test]
+fn repair_ignores_destroyed() {
+    use game::ship::{Damage, ShipTrait};
+    let mut g = game(1, 3_000);
+    g.push_ship(sniper("LowestHealth", 0).build(0), 0, 0);
+    let victim = g.push_ship(target().build(0), 1, 0);
+    let repairer = g.push_ship(repairer().build(0), 1, 0);
+    g.ship_mut(victim).unwrap().apply_damage(&Damage { kind: "laser".into(), power: 99_990 });
+    //the sniper kills the victim before the repairer of the other player fires in the same tick
+    g.tick();
+    assert_eq!(g.lane(1)[0].to_vec(), vec![repairer]);
+    assert!(g.ships().get(victim).is_none());
+}
+
+fn policy_damage(policy: &str) -> (Vec<u32>, u32) {
+    use game::ship::{Damage, ShipTrait};
+    let mut g = game(1, 3_000);
+    g.push_ship(sniper(policy, 0).build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 0);
+    let id = g.push_ship(target().build(0), 1, 0);
+    g.ship_mut(id).unwrap().apply_damage(&Damage { kind: "laser".into(), power: 1000 });
+    tick(&mut g, 10);
+    (health(&g, 1, 0), g.mothership_health(1))
+}
+
+#[test]
+fn target_policies() {
+    //enemies spawn on top of their mothership, so the first shot hits it
+    assert_eq!(policy_damage("Nearest"), (vec![99910, 99000], 999_990));
+    assert_eq!(policy_damage("LowestHealth"), (vec![100000, 98900], 1_000_000));
+    assert_eq!(policy_damage("Mothership"), (vec![100000, 99000], 999_900));
+}
+
+fn lane_reach_damage(policy: &str, lane_reach: usize) -> (u32, u32) {
+    let mut g = game(2, 3_000);
+    g.push_ship(sniper(policy, lane_reach).build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 1);
+    tick(&mut g, 10);
+    (health(&g, 1, 1)[0], g.mothership_health(1))
+}
+
+#[test]
+fn lane_reach() {
+    assert_eq!(lane_reach_damage("LowestHealth", 0), (100000, 999_900));
+    assert_eq!(lane_reach_damage("LowestHealth", 1), (99900, 1_000_000));
+    //the mothership of the own lane is closer than the ship in the other lane
+    assert_eq!(lane_reach_damage("Nearest", 1), (100000, 999_900));
+}
+
+fn dumb_fire_damage(dumb_fire: bool, pierce: u32) -> (Vec<u32>, u32) {
+    let launcher = launcher(&format!(r###""cooldown":10000,"launch_time":150,"dumb_fire":{},"pierce":{}"###, dumb_fire, pierce));
+    let launcher = armed(&weapon(5000, 100, &format!(r###""target_policy":"Mothership",{}"###, launcher)));
+    let mut g = game(1, 3_000);
+    g.push_ship(launcher.build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 0);
+    tick(&mut g, 100);
+    g.push_ship(target().build(0), 1, 0);
+    tick(&mut g, 150);
+    (health(&g, 1, 0), g.mothership_health(1))
+}
+
+#[test]
+fn dumb_fire_projectiles() {
+    assert_eq!(dumb_fire_damage(false, 0), (vec![100000, 100000], 999_000));
+    assert_eq!(dumb_fire_damage(true, 0), (vec![99000, 100000], 1_000_000));
+    assert_eq!(dumb_fire_damage(true, 1), (vec![99000, 99000], 1_000_000));
+    assert_eq!(dumb_fire_damage(true, 2), (vec![99000, 99000], 999_000));
+}
+
+#[test]
+fn body_blocking() {
+    use game::ship::ShipTrait;
+    let mut g = game(1, 3_000);
+    g.push_ship(target().build(0), 0, 0);
+    g.push_ship(target().build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 0);
+    tick(&mut g, 1000);
+    let pos = |player: usize| g.lane(player)[0].iter().map(|&s| g.ships()[s].pos_x()).collect::<Vec<_>>();
+    let (friends, enemy) = (pos(0), pos(1));
+    assert_eq!(friends[0] - friends[1], 100);
+    assert!(enemy[0] - friends[0] >= 100);
+    assert!(enemy[0] - friends[0] < 200);
+}
+
+#[test]
+fn queue_after_lane_change() {
+    use game::ship::{ShipTrait, Stance};
+    let mut g = game(2, 3_000);
+    let old = g.push_ship(target().build(0), 0, 0);
+    let young = g.push_ship(target().build(0), 0, 1);
+    assert!(g.set_stance(0, 0, Stance::Hold));
+    assert!(g.set_stance(0, 1, Stance::Hold));
+    assert!(g.move_ship(0, old, 1));
+    tick(&mut g, 100);
+    assert_eq!(g.lane(0)[1].iter().cloned().collect::<Vec<_>>(), vec![young, old]);
+    assert_eq!(g.ships()[old].pos_x(), g.ships()[young].pos_x());
+    //the older ship goes first although it joined the lane later
+    assert!(g.set_stance(0, 1, Stance::Advance));
+    tick(&mut g, 50);
+    assert!(g.ships()[old].pos_x() > g.ships()[young].pos_x());
+}
+
+#[test]
+fn status_effects() {
+    use game::ship::{EffectKind, ShipTrait};
+    let burner = |range: i32| armed(&weapon(range, 100, &format!(r###"{},"effects":[
+        {{"kind":{{"Burn":{{"kind":"plasma","power":10}}}},"duration":20}},
+        {{"kind":{{"Slow":0}},"duration":20}}
+    ]"###, laser(0))));
+    let stunner = armed(&weapon(5000, 100, &format!(r###"{},"effects":[{{"kind":"Stun","duration":1000}}]"###, laser(0))));
+    let mut g = game(1, 3_000);
+    g.push_ship(burner(5000).build(0), 0, 0);
+    g.push_ship(target().build(0), 1, 0);
+    tick(&mut g, 50);
+    let pos = g.ships()[g.lane(1)[0][0]].pos_x();
+    tick(&mut g, 50);
+    {
+        let target = &g.ships()[g.lane(1)[0][0]];
+        assert_eq!(target.effects().len(), 2);
+        assert!(target.health() < 100000 - 900);
+        assert_eq!(target.pos_x(), pos);
+    }
+    let mut g = game(1, 3_000);
+    //the burner has to advance to get in range, but is stunned on the way
+    g.push_ship(burner(2000).build(0), 0, 0);
+    g.push_ship(stunner.build(0), 1, 0);
+    tick(&mut g, 100);
+    let burner = &g.ships()[g.lane(0)[0][0]];
+    assert_eq!(burner.effects().len(), 1);
+    assert_eq!(burner.effects()[0].kind, EffectKind::Stun);
+    assert!(g.ships()[g.lane(1)[0][0]].effects().is_empty());
+}
+
+#[test]
+fn abilities() {
+    use game::ship::ShipTrait;
+    let builder = builder(r###"{"abilities":[{"cooldown":50,"effects":[{"kind":{"Haste":100},"duration":20}]}]}"###);
+    let mut g = game(2, 3_000);
+    g.push_ship(builder.build(0), 0, 0);
+    g.push_ship(builder.build(1), 0, 1);
+    assert_eq!(g.use_ability(0, 0, 1, 0), 0);
+    assert_eq!(g.use_ability(0, 1, 1, 1), 0);
+    assert_eq!(g.use_ability(0, 1, 1, 0), 1);
+    tick(&mut g, 20);
+    assert!(g.ships()[g.lane(0)[1][0]].pos_x() > g.ships()[g.lane(0)[0][0]].pos_x() + 50);
+    assert!(g.ships()[g.lane(0)[1][0]].effects().is_empty());
+    assert_eq!(g.use_ability(0, 1, 1, 0), 0);
+    tick(&mut g, 30);
+    assert_eq!(g.use_ability(0, 1, 1, 0), 1);
+}
+
+#[test]
+fn research() {
+    use game::ship::ShipTrait;
+    let builder = builder("{}");
+    let mut g = game::Game::new(&game::GameConfig {
+        upgrades: serde_json::from_str(r###"[
+            {"cost":300,"research_time":10,"effect":{"MaxSpeed":{"ship_type":0,"percent":100}}}
+        ]"###).unwrap(),
+        ..config(2, 3_000)
+    });
+    g.push_ship(builder.build(0), 0, 0);
+    assert!(!g.start_research(0, 1));
+    assert!(g.start_research(0, 0));
+    assert!(!g.start_research(0, 0));
+    assert_eq!(g.credits(0), game::STARTING_CREDITS - 300);
+    tick(&mut g, 9);
+    assert_eq!(g.research(0).current(), Some((0, 1)));
+    g.tick();
+    assert_eq!(g.research(0).current(), None);
+    assert_eq!(g.research(0).done(), &[0]);
+    assert!(g.research(1).done().is_empty());
+    assert!(!g.start_research(0, 0));
+    //only ships built after the research are faster
+    g.push_ship(builder.build(0), 0, 1);
+    let start = g.ships()[g.lane(0)[0][0]].pos_x();
+    tick(&mut g, 100);
+    let slow = g.ships()[g.lane(0)[0][0]].pos_x() - start;
+    let fast = g.ships()[g.lane(0)[1][0]].pos_x();
+    assert!(fast > slow + 300);
+    assert_eq!(g.state_hash(), game::Game::restore(g.snapshot()).state_hash());
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    spawned: usize,
+    damage: [u32; 2],
+    destroyed: Vec<(usize, usize, game::ship::ShipId)>,
+    fired: usize,
+    hits: usize,
+}
+
+impl game::GameObserver for CountingObserver {
+    fn ship_spawned(&mut self, _: usize, _: usize, _: game::ship::ShipId, _: usize) {
+        self.spawned += 1;
+    }
+    fn ship_damaged(&mut self, player: usize, _: game::ship::ShipId, _: &str, damage: u32) {
+        self.damage[player] += damage;
+    }
+    fn ship_destroyed(&mut self, player: usize, lane: usize, ship: game::ship::ShipId) {
+        self.destroyed.push((player, lane, ship));
+    }
+    fn projectile_fired(&mut self, _: usize, _: usize) {
+        self.fired += 1;
+    }
+    fn projectile_hit(&mut self, _: usize, _: game::ship::ShipId) {
+        self.hits += 1;
+    }
+}
+
+#[test]
+fn observer() {
+    let launcher = splash_launcher("null");
+    let target: game::ship::BaseShipBuilder = serde_json::from_str(BUILDER_JSON).unwrap();
+    let config = config(1, 3_000);
+    let mut observed = game::Game::new(&config);
+    let mut plain = game::Game::new(&config);
+    let mut o = CountingObserver::default();
+    observed.push_ship_observed(launcher.build(0), 0, 0, &mut o);
+    let id = observed.push_ship_observed(target.build(0), 1, 0, &mut o);
+    plain.push_ship(launcher.build(0), 0, 0);
+    plain.push_ship(target.build(0), 1, 0);
+    for _ in 0..3000 {
+        observed.tick_observed(&mut o);
+        plain.tick();
+    }
+    assert_eq!(observed.state_hash(), plain.state_hash());
+    assert_eq!(o.spawned, 2);
+    assert_eq!(o.destroyed, vec![(1, 0, id)]);
+    assert_eq!(o.fired, 3);
+    assert_eq!(o.hits, 3);
+    assert_eq!(o.damage, [0, 1000]);
+}
+
+#[test]
+fn ship_ids() {
+    use game::ship::{Damage, ShipTrait};
+    let target = target();
+    let mut g = game(1, 3_000);
+    let id = g.push_ship(target.build(0), 0, 0);
+    g.ship_mut(id).unwrap().apply_damage(&Damage { kind: "laser".into(), power: 1_000_000 });
+    g.tick();
+    assert!(g.lane(0)[0].is_empty());
+    assert!(g.ships().get(id).is_none());
+    //the slot is reused, but the old id doesn't refer to the new ship
+    let new_id = g.push_ship(target.build(0), 0, 0);
+    assert!(new_id != id);
+    assert!(g.ships().get(id).is_none());
+    assert_eq!(g.ships()[new_id].id(), new_id);
+}
+
+#[cfg(not(feature = "graphics"))]
+#[test]
+fn game_is_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<game::Game>();
+}
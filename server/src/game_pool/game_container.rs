@@ -1,12 +1,19 @@
 use std::sync::mpsc::{TryRecvError, Receiver};
 use std::mem;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
 use time::SteadyTime;
-use common::game::Game;
+use common::game::{Game, GameConfig};
 use common::game::ship::BaseShipBuilder;
 use common::protocol::*;
+use common::serde_json;
 use server::Stream;
 use super::GameStartArg;
 
+const MAX_STATE_HASHES: usize = 32;
+
 pub enum ReadReady {
     Read1,
     Read2,
@@ -19,22 +26,45 @@ pub struct GameContainer {
     game: Game,
     builders: [Vec<BaseShipBuilder>; 2],
     events: Vec<(usize, ServerEvent)>,
+    state_hashes: VecDeque<(usize, u64)>,
     tick: usize,
     start: SteadyTime,
     last_send: usize,
+    replay: Option<(PathBuf, Replay)>,
+    stats: MatchStats,
 }
 
 impl GameContainer {
-    pub fn new(players: GameStartArg, poll: Receiver<ReadReady>) -> Self {
+    pub fn new(config: &GameConfig, players: GameStartArg, poll: Receiver<ReadReady>, replay_path: Option<PathBuf>) -> Self {
+        let ((stream1, start1), (stream2, start2)) = players;
+        let builders = [start1.ships, start2.ships];
+        let motherships = [start1.mothership, start2.mothership];
+        let mut game = Game::new(config);
+        game.set_mothership(0, &motherships[0]);
+        game.set_mothership(1, &motherships[1]);
         GameContainer {
             poll: poll,
-            streams: [(players.0).0, (players.1).0],
-            game: Game::new(4, 10_000),
-            builders: [(players.0).1, (players.1).1],
+            streams: [stream1, stream2],
+            game: game,
+            replay: replay_path.map(|path| (path, Replay::new(config.clone(), builders.clone(), motherships))),
+            builders: builders,
             events: Vec::new(),
+            state_hashes: VecDeque::new(),
             tick: 0,
             start: SteadyTime::now(),
             last_send: 0,
+            stats: MatchStats::default(),
+        }
+    }
+
+    pub fn save_replay(&self) {
+        if let Some((ref path, ref replay)) = self.replay {
+            let res = File::create(path).map_err(serde_json::Error::from)
+                .and_then(|f| serde_json::to_writer(&mut BufWriter::new(f), replay));
+            match res {
+                Ok(()) => info!("saved replay to {:?}", path),
+                Err(e) => warn!("cannot save replay to {:?}: {:?}", path, e),
+            }
         }
     }
 
@@ -58,14 +88,72 @@ impl GameContainer {
             match self.streams[player].read() {
                 Some(Ok(ClientGame::SpawnShip { id, lane })) => {
                     if lane < self.game.lane_count() && id < self.builders[player].len() {
-                        self.game.push_ship(self.builders[player][id].build(), player, lane);
-                        self.events.push((self.tick, ServerEvent::SpawnShip { player: player, id: id, lane: lane }));
+                        if self.game.try_spend(player, self.builders[player][id].cost()) {
+                            let ship = self.builders[player][id].build(id);
+                            self.game.push_ship_observed(ship, player, lane, &mut self.stats);
+                            self.events.push((self.tick, ServerEvent::SpawnShip { player: player, id: id, lane: lane }));
+                        } else {
+                            debug!("player {} cannot afford ship {}", player, id);
+                        }
+                        return true;
+                    } else {
+                        self.streams[player ^ 1].write(&ServerGame::OtherDisconnect).is_ok();
+                        return false;
+                    }
+                },
+                Some(Ok(ClientGame::MoveShip { ship, lane })) => {
+                    //the ship may have been destroyed since the client sent this
+                    if self.game.move_ship(player, ship, lane) {
+                        self.events.push((self.tick, ServerEvent::MoveShip { player: player, ship: ship, lane: lane }));
+                    } else {
+                        debug!("player {} cannot move ship {:?} to lane {}", player, ship, lane);
+                    }
+                    return true;
+                },
+                Some(Ok(ClientGame::SetStance { lane, stance })) => {
+                    if self.game.set_stance(player, lane, stance) {
+                        self.events.push((self.tick, ServerEvent::SetStance { player: player, lane: lane, stance: stance }));
                         return true;
                     } else {
                         self.streams[player ^ 1].write(&ServerGame::OtherDisconnect).is_ok();
                         return false;
                     }
                 },
+                Some(Ok(ClientGame::UseAbility { lane, ship_type, ability })) => {
+                    if lane >= self.game.lane_count() {
+                        self.streams[player ^ 1].write(&ServerGame::OtherDisconnect).is_ok();
+                        return false;
+                    }
+                    //all ships of the type may be on cooldown or destroyed
+                    if self.game.use_ability(player, lane, ship_type, ability) > 0 {
+                        self.events.push((self.tick, ServerEvent::UseAbility { player: player, lane: lane, ship_type: ship_type, ability: ability }));
+                    } else {
+                        debug!("player {} cannot use ability {} of ship {} in lane {}", player, ability, ship_type, lane);
+                    }
+                    return true;
+                },
+                Some(Ok(ClientGame::Research { upgrade })) => {
+                    //the upgrade may be unknown, already researched or too expensive
+                    if self.game.start_research(player, upgrade) {
+                        self.events.push((self.tick, ServerEvent::Research { player: player, upgrade: upgrade }));
+                    } else {
+                        debug!("player {} cannot research upgrade {}", player, upgrade);
+                    }
+                    return true;
+                },
+                Some(Ok(ClientGame::StateHash { tick, hash })) => {
+                    match self.state_hashes.iter().find(|&&(t, _)| t == tick) {
+                        Some(&(_, own_hash)) => if own_hash != hash {
+                            info!("player {} desynced at tick {}", player, tick);
+                            let msg = ServerGame::Desync { tick: tick };
+                            if self.send_or_disconnect(0, &msg) {
+                                self.send_or_disconnect(1, &msg);
+                            }
+                            return false;
+                        },
+                        None => debug!("no state hash for tick {} reported by player {}", tick, player),
+                    }
+                },
                 Some(Err(_)) => {
                     self.streams[player ^ 1].write(&ServerGame::OtherDisconnect).is_ok();
                     return false;
@@ -76,16 +164,25 @@ impl GameContainer {
     }
 
     fn update(&mut self) -> bool {
-        while self.tick < (SteadyTime::now() - self.start).num_milliseconds() as usize / 20 {
-            self.game.tick();
+        let tick_length = self.game.config().tick_length as usize;
+        while self.tick < (SteadyTime::now() - self.start).num_milliseconds() as usize / tick_length {
+            self.game.tick_observed(&mut self.stats);
             self.tick += 1;
+            if self.tick % STATE_HASH_INTERVAL == 0 {
+                if self.state_hashes.len() >= MAX_STATE_HASHES {
+                    self.state_hashes.pop_front();
+                }
+                self.state_hashes.push_back((self.tick, self.game.state_hash()));
+            }
         }
         if self.game.winner().is_some() {
+            self.stats.duration = self.tick;
+            let end = ServerGame::End(self.stats.clone());
             self.flush_events()
-                && self.send_or_disconnect(0, &ServerGame::End)
-                && self.send_or_disconnect(1, &ServerGame::End);
+                && self.send_or_disconnect(0, &end)
+                && self.send_or_disconnect(1, &end);
             false
-        } else if self.tick - self.last_send >= 16 {
+        } else if self.tick - self.last_send >= self.game.config().update_interval {
             self.flush_events()
         } else {
             true
@@ -93,10 +190,14 @@ impl GameContainer {
     }
 
     fn flush_events(&mut self) -> bool {
-        let msg = ServerGame::Update(ServerGameUpdate {
+        let update = ServerGameUpdate {
             tick: self.tick,
             events: mem::replace(&mut self.events, Vec::new())
-        });
+        };
+        if let Some((_, ref mut replay)) = self.replay {
+            replay.updates.push(update.clone());
+        }
+        let msg = ServerGame::Update(update);
         self.last_send = self.tick;
         self.send_or_disconnect(0, &msg) && self.send_or_disconnect(1, &msg)
     }
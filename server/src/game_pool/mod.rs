@@ -4,21 +4,25 @@ use std::sync::Arc;
 use std::sync::mpsc::{Sender, Receiver, channel, RecvTimeoutError};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
-use time::{SteadyTime, Duration};
+use std::path::PathBuf;
+use time::{SteadyTime, Duration, get_time};
 use mio::tcp::TcpStream;
 use common::protocol::*;
-use common::game::ship::BaseShipBuilder;
+use common::game::GameConfig;
 use self::game_container::{ReadReady, GameContainer};
 
-type GameStartArg = ((BufStream<TcpStream>, Vec<BaseShipBuilder>), (BufStream<TcpStream>, Vec<BaseShipBuilder>));
+type GameStartArg = ((BufStream<TcpStream>, ClientStart), (BufStream<TcpStream>, ClientStart));
+type GameMessage = (GameConfig, GameStartArg, Receiver<ReadReady>, Option<PathBuf>);
 
 pub struct GameThreadPool {
     threads: Vec<GameThread>,
+    replay_dir: Option<PathBuf>,
+    games_started: usize,
 }
 
 struct GameThread {
     game_count: Arc<AtomicUsize>,
-    sender: Sender<(GameStartArg, Receiver<ReadReady>)>,
+    sender: Sender<GameMessage>,
 }
 
 impl GameThread {
@@ -32,19 +36,19 @@ impl GameThread {
         thread::spawn(move || run_games(rec, count_clone));
         r
     }
-    fn push(&mut self, gsa: GameStartArg, poll_rec: Receiver<ReadReady>) {
-        self.sender.send((gsa, poll_rec)).unwrap();
+    fn push(&mut self, config: GameConfig, gsa: GameStartArg, poll_rec: Receiver<ReadReady>, replay_path: Option<PathBuf>) {
+        self.sender.send((config, gsa, poll_rec, replay_path)).unwrap();
     }
 }
 
-fn run_games(rec: Receiver<(GameStartArg, Receiver<ReadReady>)>, game_count: Arc<AtomicUsize>) {
+fn run_games(rec: Receiver<GameMessage>, game_count: Arc<AtomicUsize>) {
     let mut games = Vec::new();
     loop {
         let rec_end_time = SteadyTime::now() + Duration::milliseconds(10);
         while let Ok(timeout) = (rec_end_time - SteadyTime::now()).to_std() {
             match rec.recv_timeout(timeout) {
-                Ok((start_arg, poll_rec)) => {
-                    games.push(GameContainer::new(start_arg, poll_rec));
+                Ok((config, start_arg, poll_rec, replay_path)) => {
+                    games.push(GameContainer::new(&config, start_arg, poll_rec, replay_path));
                     game_count.fetch_add(1, Ordering::Relaxed);
                 },
                 Err(RecvTimeoutError::Timeout) => break,
@@ -57,7 +61,7 @@ fn run_games(rec: Receiver<(GameStartArg, Receiver<ReadReady>)>, game_count: Arc
                 i += 1;
             } else {
                 debug!("removed game container");
-                games.swap_remove(i);
+                games.swap_remove(i).save_replay();
                 game_count.fetch_sub(1, Ordering::Relaxed);
             }
         }
@@ -66,17 +70,23 @@ fn run_games(rec: Receiver<(GameStartArg, Receiver<ReadReady>)>, game_count: Arc
 }
 
 impl GameThreadPool {
-    pub fn new(thread_count: usize) -> Self {
+    pub fn new(thread_count: usize, replay_dir: Option<PathBuf>) -> Self {
         assert! (thread_count > 0);
         GameThreadPool {
-            threads: (0..thread_count).map(|_| { GameThread::new() }).collect()
+            threads: (0..thread_count).map(|_| { GameThread::new() }).collect(),
+            replay_dir: replay_dir,
+            games_started: 0,
         }
     }
 
-    pub fn push(&mut self, players: GameStartArg) -> (GameHandle, GameHandle) {
+    pub fn push(&mut self, config: GameConfig, players: GameStartArg) -> (GameHandle, GameHandle) {
         let (send, rec) = channel();
+        let replay_path = self.replay_dir.as_ref().map(|dir| {
+            dir.join(format!("{}-{}.json", get_time().sec, self.games_started))
+        });
+        self.games_started += 1;
         let thread = self.threads.iter_mut().min_by_key(|gt| gt.game_count.load(Ordering::Relaxed)).unwrap();
-        thread.push(players, rec);
+        thread.push(config, players, rec, replay_path);
         (GameHandle {
             sender: send.clone(),
             player_num: 0
@@ -6,11 +6,13 @@ extern crate log;
 extern crate env_logger;
 
 use std::env::args;
+use std::path::PathBuf;
 
 mod game_pool;
 mod server;
 
 pub fn main() {
     env_logger::init().expect("initializing logger");
-    server::run(&args().nth(1).expect("expected 1 argument"), 2);
+    let replay_dir = args().nth(2).map(PathBuf::from);
+    server::run(&args().nth(1).expect("expected at least 1 argument"), 2, replay_dir);
 }
\ No newline at end of file
@@ -2,11 +2,12 @@ use std::net::SocketAddr;
 use std::io::ErrorKind;
 use std::str::FromStr;
 use std::mem;
+use std::path::PathBuf;
 use mio::tcp::{TcpStream, TcpListener};
 use mio::{Poll, Token, Ready, PollOpt, Events};
 use common::protocol::*;
 use game_pool::{GameHandle, GameThreadPool};
-use common::game::ship::BaseShipBuilder;
+use common::game::GameConfig;
 use common::serde_json::from_slice;
 use common::serde::Serialize;
 use std::fmt::{Display, Debug};
@@ -17,9 +18,9 @@ pub type Stream = BufStream<TcpStream>;
 enum PlayerState {
     Empty,
     New { stream: Stream },
-    Waiting { stream: Stream, join_id: u32 },
-    Preparing { stream: Stream, other_id: usize, second: bool },
-    Ready { stream: Stream, other_id: usize, builders: Vec<BaseShipBuilder>, second: bool },
+    Waiting { stream: Stream, join_id: u32, config: GameConfig },
+    Preparing { stream: Stream, other_id: usize, second: bool, config: GameConfig },
+    Ready { stream: Stream, other_id: usize, start: ClientStart, second: bool, config: GameConfig },
     Playing { game: GameHandle, other_id: usize },
 
     Locked,
@@ -82,15 +83,15 @@ impl Server {
             },
             PlayerState::Empty => unreachable!(),
             PlayerState::Locked => unreachable!(),
-            PlayerState::Waiting { mut stream, join_id } => {
+            PlayerState::Waiting { mut stream, join_id, config } => {
                 if stream.read_raw().is_some() {
                     info!("message from {} while waiting", id);
                     (false, PlayerState::Empty)
                 } else {
-                    (false, PlayerState::Waiting { stream: stream, join_id: join_id })
+                    (false, PlayerState::Waiting { stream: stream, join_id: join_id, config: config })
                 }
             },
-            PlayerState::Ready { mut stream, other_id, builders, second } => {
+            PlayerState::Ready { mut stream, other_id, start, second, config } => {
                 if stream.read_raw().is_some() {
                     info!("message from {} while preparing", id);
                     (false, PlayerState::Empty)
@@ -98,14 +99,15 @@ impl Server {
                     (false, PlayerState::Ready {
                         stream: stream,
                         other_id: other_id,
-                        builders: builders,
-                        second: second
+                        start: start,
+                        second: second,
+                        config: config,
                     })
                 }
             },
 
-            PlayerState::Preparing { stream, other_id, second } => {
-                self.receive_preparing(stream, id, other_id, second)
+            PlayerState::Preparing { stream, other_id, second, config } => {
+                self.receive_preparing(stream, id, other_id, second, config)
             },
             PlayerState::Playing { game, other_id } => {
                 if game.try_read().is_err() {
@@ -119,14 +121,15 @@ impl Server {
         self.players[id] = ps;
         ret
     }
-    fn receive_preparing(&mut self, mut stream: Stream, id: usize, other_id: usize, second: bool) -> (bool, PlayerState) {
+    fn receive_preparing(&mut self, mut stream: Stream, id: usize, other_id: usize, second: bool, config: GameConfig)
+                         -> (bool, PlayerState) {
         match stream.read_raw() {
             Some(Ok(raw_msg)) => {
                 match from_slice(&raw_msg) {
-                    Ok(ClientStart { ships }) => {
+                    Ok(start) => {
                         let other_state = mem::replace(&mut self.players[other_id], PlayerState::Locked);
                         let (ret, ps1, ps2) = match other_state {
-                            PlayerState::Preparing { stream: mut stream2, second: second2, other_id } => {
+                            PlayerState::Preparing { stream: mut stream2, second: second2, other_id, .. } => {
                                 if let Err(e) = stream2.write_raw(&raw_msg) {
                                     self.remove_send_err(&stream2, other_id, e);
                                     (false, PlayerState::Empty, PlayerState::Empty)
@@ -134,29 +137,31 @@ impl Server {
                                     (true,
                                      PlayerState::Ready {
                                          stream: stream,
-                                         builders: ships,
+                                         start: start,
                                          other_id: other_id,
-                                         second: second
+                                         second: second,
+                                         config: config.clone(),
                                      }, PlayerState::Preparing {
                                         stream: stream2,
                                         other_id: id,
-                                        second: second2
+                                        second: second2,
+                                        config: config,
                                     })
                                 }
                             },
-                            PlayerState::Ready { stream: mut stream2, builders: builder2, second: second2, .. } => {
+                            PlayerState::Ready { stream: mut stream2, start: start2, second: second2, .. } => {
                                 if let Err(e) = stream2.write_raw(&raw_msg) {
                                     self.remove_send_err(&stream2, other_id, e);
                                     (false, PlayerState::Empty, PlayerState::Empty)
                                 } else {
                                     info!("{} and {} started playing", id, other_id);
                                     if second2 {
-                                        let (g1, g2) = self.game_pool.push(((stream, ships), (stream2, builder2)));
+                                        let (g1, g2) = self.game_pool.push(config, ((stream, start), (stream2, start2)));
                                         (true,
                                          PlayerState::Playing { game: g1, other_id: other_id },
                                          PlayerState::Playing { game: g2, other_id: id })
                                     } else {
-                                        let (g2, g1) = self.game_pool.push(((stream2, builder2), (stream, ships)));
+                                        let (g2, g1) = self.game_pool.push(config, ((stream2, start2), (stream, start)));
                                         (true,
                                          PlayerState::Playing { game: g1, other_id: other_id },
                                          PlayerState::Playing { game: g2, other_id: id })
@@ -181,7 +186,7 @@ impl Server {
                 (false, PlayerState::Empty)
             },
             None => {
-                (false, PlayerState::Preparing { stream: stream, other_id: other_id, second: second })
+                (false, PlayerState::Preparing { stream: stream, other_id: other_id, second: second, config: config })
             }
         }
     }
@@ -203,11 +208,14 @@ impl Server {
                     }
                 }
             },
-            Some(Ok(ClientJoin::Create)) => {
+            Some(Ok(ClientJoin::Create(config))) => {
                 let join_id = id as u32;
-                if self.send_or_remove(id, &mut stream, &ServerJoin::Created(join_id)) {
+                if !config.is_valid() {
+                    info!("{} tried to create game with invalid config {:?}", id, config);
+                    (false, PlayerState::Empty)
+                } else if self.send_or_remove(id, &mut stream, &ServerJoin::Created(join_id)) {
                     info!("{} creates game", id);
-                    (true, PlayerState::Waiting { stream: stream, join_id: join_id })
+                    (true, PlayerState::Waiting { stream: stream, join_id: join_id, config: config })
                 } else {
                     (false, PlayerState::Empty)
                 }
@@ -220,12 +228,12 @@ impl Server {
         }
     }
     fn join_game(&mut self, mut stream: Stream, id: usize, id2: usize) -> (bool, PlayerState) {
-        if let PlayerState::Waiting { stream: mut stream2, .. } = mem::replace(&mut self.players[id2], PlayerState::Locked) {
-            if self.send_or_remove(id2, &mut stream2, &ServerJoin::Start(1)) {
-                if self.send_or_remove(id, &mut stream, &ServerJoin::Start(0)) {
+        if let PlayerState::Waiting { stream: mut stream2, config, .. } = mem::replace(&mut self.players[id2], PlayerState::Locked) {
+            if self.send_or_remove(id2, &mut stream2, &ServerJoin::Start(1, config.clone())) {
+                if self.send_or_remove(id, &mut stream, &ServerJoin::Start(0, config.clone())) {
                     info!("{} joins game created by {}", id, id2);
-                    self.players[id2] = PlayerState::Preparing { stream: stream2, other_id: id, second: true };
-                    (true, PlayerState::Preparing { stream: stream, other_id: id2, second: false })
+                    self.players[id2] = PlayerState::Preparing { stream: stream2, other_id: id, second: true, config: config.clone() };
+                    (true, PlayerState::Preparing { stream: stream, other_id: id2, second: false, config: config })
                 } else {
                     info!("{} was dropped because of failed join by {}", id2, id);
                     self.drop_preparing(id2);
@@ -301,10 +309,10 @@ impl Server {
 const MAX_PLAYERS: usize = 1000;
 const LISTENER_TOKEN: usize = 1000;
 
-pub fn run(address: &str, num_threads: usize) -> ! {
+pub fn run(address: &str, num_threads: usize, replay_dir: Option<PathBuf>) -> ! {
     let parsed_address = SocketAddr::from_str(address).expect(&format!("parsing socket address {:?}", address));
     let mut server = Server {
-        game_pool: GameThreadPool::new(num_threads),
+        game_pool: GameThreadPool::new(num_threads, replay_dir),
         players: Vec::new(),
         listener: TcpListener::bind(&parsed_address).expect(&format!("cannot open tcp listener for {:?}", parsed_address)),
         poll: Poll::new().expect("cannot create poll"),